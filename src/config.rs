@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::llm::config::HttpConfig;
+use crate::package::signing::TrustConfig;
+use crate::skill::lint::LintConfig;
+use crate::skill::selector::SelectorConfig;
+
+/// Optional on-disk configuration, read from `GENAI_CONFIG` or `~/GenAI/config.yaml`.
+/// Environment variables always take precedence over values from this file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GenAiConfig {
+    pub providers: ProvidersConfig,
+    pub trust: TrustConfig,
+    pub selector: SelectorConfig,
+    pub lint: LintConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProvidersConfig {
+    pub gemini: ProviderConfig,
+    pub openai: ProviderConfig,
+    pub ollama: ProviderConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProviderConfig {
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    pub http: HttpConfig,
+}
+
+impl GenAiConfig {
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::from_path(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse config {}", path.display()))
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var("GENAI_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        std::env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(format!("{home}/GenAI/config.yaml")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GenAiConfig;
+
+    #[test]
+    fn parses_provider_http_section() {
+        let yaml = r#"
+providers:
+  gemini:
+    model: gemini-2.5-pro
+    http:
+      timeout_secs: 300
+      proxy: http://proxy.internal:3128
+      headers:
+        X-Gateway-Key: secret
+"#;
+        let cfg: GenAiConfig = serde_yaml::from_str(yaml).expect("config should parse");
+        let gemini = cfg.providers.gemini;
+        assert_eq!(gemini.model.as_deref(), Some("gemini-2.5-pro"));
+        assert_eq!(gemini.http.timeout_secs, 300);
+        assert_eq!(gemini.http.connect_timeout_secs, 10);
+        assert_eq!(
+            gemini.http.proxy.as_deref(),
+            Some("http://proxy.internal:3128")
+        );
+        assert_eq!(gemini.http.headers["X-Gateway-Key"], "secret");
+    }
+}
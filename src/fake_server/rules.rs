@@ -0,0 +1,178 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+/// Scripted behaviour for `genai fake-server`, loaded from YAML.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FakeRules {
+    /// When set, requests must carry this `x-goog-api-key` or get a 401.
+    pub api_key: Option<String>,
+    pub models: Vec<FakeModel>,
+    pub rules: Vec<FakeRule>,
+    /// Used when no rule matches; echoes the prompt when absent.
+    pub default: Option<FakeResponse>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FakeModel {
+    pub name: String,
+    pub input_token_limit: Option<u64>,
+    pub output_token_limit: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FakeRule {
+    #[serde(default, rename = "match")]
+    pub matcher: FakeMatch,
+    pub response: FakeResponse,
+    /// Apply at most this many times, e.g. to fail only the first request.
+    pub times: Option<u32>,
+    #[serde(skip)]
+    hits: AtomicU32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FakeMatch {
+    /// `generateContent`, `streamGenerateContent`, `countTokens`, `batchEmbedContents` or `models`.
+    pub method: Option<String>,
+    pub model: Option<String>,
+    pub prompt_contains: Option<String>,
+    pub prompt_regex: Option<String>,
+    pub header: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FakeResponse {
+    pub text: Option<String>,
+    pub candidates: Vec<String>,
+    pub thoughts: Vec<String>,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub delay_ms: Option<u64>,
+    pub total_tokens: Option<u64>,
+}
+
+/// What the server knows about an incoming call when matching rules.
+pub struct FakeCall<'a> {
+    pub method: &'a str,
+    pub model: &'a str,
+    pub prompt: &'a str,
+    pub headers: &'a [(String, String)],
+}
+
+impl FakeRules {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fake-server rules {path}"))?;
+        Self::from_yaml(&content)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let rules: FakeRules =
+            serde_yaml::from_str(content).context("Failed to parse fake-server rules YAML")?;
+        for rule in &rules.rules {
+            if let Some(pattern) = &rule.matcher.prompt_regex {
+                Regex::new(pattern).with_context(|| format!("Invalid prompt_regex '{pattern}'"))?;
+            }
+        }
+        Ok(rules)
+    }
+
+    /// First matching rule that still has uses left.
+    pub fn respond(&self, call: &FakeCall) -> Option<FakeResponse> {
+        for rule in &self.rules {
+            if !rule.matcher.matches(call) {
+                continue;
+            }
+            if let Some(times) = rule.times {
+                if rule.hits.fetch_add(1, Ordering::SeqCst) >= times {
+                    continue;
+                }
+            }
+            return Some(rule.response.clone());
+        }
+        self.default.clone()
+    }
+}
+
+impl FakeMatch {
+    fn matches(&self, call: &FakeCall) -> bool {
+        if self.method.as_deref().is_some_and(|m| m != call.method) {
+            return false;
+        }
+        if self.model.as_deref().is_some_and(|m| m != call.model) {
+            return false;
+        }
+        if self
+            .prompt_contains
+            .as_deref()
+            .is_some_and(|needle| !call.prompt.contains(needle))
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.prompt_regex {
+            if !Regex::new(pattern).is_ok_and(|re| re.is_match(call.prompt)) {
+                return false;
+            }
+        }
+        if let Some(expected) = &self.header {
+            let (name, value) = expected.split_once(':').unwrap_or((expected, ""));
+            let found = call.headers.iter().any(|(n, v)| {
+                n.eq_ignore_ascii_case(name.trim())
+                    && (value.trim().is_empty() || v == value.trim())
+            });
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FakeCall, FakeRules};
+
+    #[test]
+    fn rules_match_in_order_and_respect_times() {
+        let rules = FakeRules::from_yaml(
+            r#"
+rules:
+  - match: { prompt_contains: commit }
+    times: 1
+    response: { status: 503, error: overloaded }
+  - match: { prompt_contains: commit, model: gemini-2.5-flash }
+    response: { text: "feat: add parser" }
+default:
+  text: fallback
+"#,
+        )
+        .expect("rules should parse");
+
+        let call = FakeCall {
+            method: "generateContent",
+            model: "gemini-2.5-flash",
+            prompt: "write a commit message",
+            headers: &[],
+        };
+        assert_eq!(rules.respond(&call).unwrap().status, Some(503));
+        assert_eq!(
+            rules.respond(&call).unwrap().text.as_deref(),
+            Some("feat: add parser")
+        );
+
+        let other = FakeCall {
+            prompt: "hello",
+            ..call
+        };
+        assert_eq!(
+            rules.respond(&other).unwrap().text.as_deref(),
+            Some("fallback")
+        );
+    }
+}
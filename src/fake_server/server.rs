@@ -0,0 +1,442 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use tracing::{debug, info, warn};
+
+use crate::fake_server::rules::{FakeCall, FakeResponse, FakeRules};
+use crate::llm::mock::hashed_embedding;
+use crate::llm::tokens::estimate_tokens;
+
+/// Minimal HTTP/1.1 server speaking the subset of the Gemini REST API genai uses.
+pub struct FakeServer {
+    listener: TcpListener,
+    rules: Arc<FakeRules>,
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+struct HttpResponse {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl HttpResponse {
+    fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(
+            status,
+            json!({ "error": { "code": status, "message": message, "status": status_text(status) } }),
+        )
+    }
+}
+
+impl FakeServer {
+    pub fn bind(addr: &str, rules: FakeRules) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .with_context(|| format!("Failed to bind fake server {addr}"))?;
+        Ok(Self {
+            listener,
+            rules: Arc::new(rules),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves forever, one thread per connection.
+    pub fn serve(self) -> Result<()> {
+        info!(
+            "Fake Gemini server listening on http://{}",
+            self.local_addr()?
+        );
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("Fake server accept failed: {err}");
+                    continue;
+                }
+            };
+            let rules = Arc::clone(&self.rules);
+            std::thread::spawn(move || {
+                if let Err(err) = handle_connection(stream, &rules) {
+                    warn!("Fake server connection failed: {err}");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    pub fn spawn(self) -> JoinHandle<Result<()>> {
+        std::thread::spawn(move || self.serve())
+    }
+}
+
+fn handle_connection(stream: TcpStream, rules: &FakeRules) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = read_request(&mut reader)?;
+    debug!(method = %request.method, path = %request.path, "Fake server request");
+
+    let mut stream = stream;
+    route(&request, rules, &mut stream)
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<HttpRequest> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| anyhow!("Empty request"))?
+        .to_string();
+    let target = parts
+        .next()
+        .ok_or_else(|| anyhow!("Missing request target"))?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    let mut content_length = 0usize;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let (name, value) = (name.trim().to_string(), value.trim().to_string());
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().context("Invalid Content-Length")?;
+            }
+            headers.push((name, value));
+        }
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(HttpRequest {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn route(request: &HttpRequest, rules: &FakeRules, stream: &mut TcpStream) -> Result<()> {
+    if let Some(expected) = &rules.api_key {
+        let provided = request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("x-goog-api-key"))
+            .map(|(_, value)| value.as_str());
+        if provided != Some(expected.as_str()) {
+            return write_response(stream, &HttpResponse::error(401, "API key not valid"));
+        }
+    }
+
+    let path = request.path.trim_end_matches('/');
+    if request.method == "GET" && path == "/v1beta/models" {
+        return handle_models(request, rules, stream);
+    }
+
+    let Some((model, method)) = path
+        .strip_prefix("/v1beta/models/")
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return write_response(stream, &HttpResponse::error(404, "Not found"));
+    };
+    if request.method != "POST" {
+        return write_response(stream, &HttpResponse::error(405, "Method not allowed"));
+    }
+
+    let body: Value = serde_json::from_str(&request.body).unwrap_or(Value::Null);
+    let prompt = prompt_text(&body);
+    let call = FakeCall {
+        method,
+        model,
+        prompt: &prompt,
+        headers: &request.headers,
+    };
+    let scripted = rules.respond(&call);
+    if let Some(response) = &scripted {
+        if let Some(delay) = response.delay_ms {
+            std::thread::sleep(Duration::from_millis(delay));
+        }
+        if let Some(status) = response.status.filter(|status| *status >= 400) {
+            let message = response.error.as_deref().unwrap_or("Injected error");
+            return write_response(stream, &HttpResponse::error(status, message));
+        }
+    }
+    let scripted = scripted.unwrap_or_default();
+
+    match method {
+        "generateContent" => {
+            let count = body["generationConfig"]["candidateCount"]
+                .as_u64()
+                .unwrap_or(1);
+            let payload = generate_payload(&scripted, &prompt, count as usize);
+            write_response(stream, &HttpResponse::json(200, payload))
+        }
+        "streamGenerateContent" => handle_stream(request, &scripted, &prompt, stream),
+        "countTokens" => {
+            let total = scripted
+                .total_tokens
+                .unwrap_or_else(|| estimate_tokens(&prompt));
+            write_response(
+                stream,
+                &HttpResponse::json(200, json!({ "totalTokens": total })),
+            )
+        }
+        "batchEmbedContents" => {
+            let embeddings = body["requests"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|request| {
+                    let text = prompt_text(&json!({ "contents": [request["content"]] }));
+                    json!({ "values": hashed_embedding(&text) })
+                })
+                .collect::<Vec<_>>();
+            write_response(
+                stream,
+                &HttpResponse::json(200, json!({ "embeddings": embeddings })),
+            )
+        }
+        _ => write_response(stream, &HttpResponse::error(404, "Unknown method")),
+    }
+}
+
+fn handle_models(request: &HttpRequest, rules: &FakeRules, stream: &mut TcpStream) -> Result<()> {
+    let call = FakeCall {
+        method: "models",
+        model: "",
+        prompt: "",
+        headers: &request.headers,
+    };
+    if let Some(response) = rules.respond(&call) {
+        if let Some(status) = response.status.filter(|status| *status >= 400) {
+            let message = response.error.as_deref().unwrap_or("Injected error");
+            return write_response(stream, &HttpResponse::error(status, message));
+        }
+    }
+
+    let models = rules
+        .models
+        .iter()
+        .map(|model| {
+            json!({
+                "name": format!("models/{}", model.name),
+                "inputTokenLimit": model.input_token_limit,
+                "outputTokenLimit": model.output_token_limit,
+                "supportedGenerationMethods": ["generateContent", "countTokens", "batchEmbedContents"],
+            })
+        })
+        .collect::<Vec<_>>();
+    debug!(query = %request.query, "Serving model list");
+    write_response(
+        stream,
+        &HttpResponse::json(200, json!({ "models": models })),
+    )
+}
+
+fn handle_stream(
+    request: &HttpRequest,
+    scripted: &FakeResponse,
+    prompt: &str,
+    stream: &mut TcpStream,
+) -> Result<()> {
+    let text = answer_texts(scripted, prompt, 1).remove(0);
+    let chunks = text
+        .split_inclusive(' ')
+        .map(|chunk| json!({ "candidates": [candidate(chunk, &[])] }))
+        .collect::<Vec<_>>();
+
+    if !request.query.contains("alt=sse") {
+        return write_response(stream, &HttpResponse::json(200, Value::Array(chunks)));
+    }
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n"
+    )?;
+    for chunk in chunks {
+        write!(stream, "data: {chunk}\r\n\r\n")?;
+        stream.flush()?;
+    }
+    Ok(())
+}
+
+fn prompt_text(body: &Value) -> String {
+    body["contents"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|content| content["parts"].as_array().into_iter().flatten())
+        .filter_map(|part| part["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn answer_texts(scripted: &FakeResponse, prompt: &str, count: usize) -> Vec<String> {
+    if !scripted.candidates.is_empty() {
+        return scripted.candidates.clone();
+    }
+    let text = scripted
+        .text
+        .clone()
+        .unwrap_or_else(|| format!("[fake] {prompt}"));
+    vec![text; count.max(1)]
+}
+
+fn candidate(text: &str, thoughts: &[String]) -> Value {
+    let mut parts = thoughts
+        .iter()
+        .map(|thought| json!({ "text": thought, "thought": true }))
+        .collect::<Vec<_>>();
+    parts.push(json!({ "text": text }));
+    json!({ "content": { "role": "model", "parts": parts }, "finishReason": "STOP" })
+}
+
+fn generate_payload(scripted: &FakeResponse, prompt: &str, count: usize) -> Value {
+    let answers = answer_texts(scripted, prompt, count);
+    let prompt_tokens = estimate_tokens(prompt);
+    let output_tokens = answers.iter().map(|a| estimate_tokens(a)).sum::<u64>();
+    let thinking_tokens = scripted
+        .thoughts
+        .iter()
+        .map(|t| estimate_tokens(t))
+        .sum::<u64>();
+
+    json!({
+        "candidates": answers
+            .iter()
+            .map(|answer| candidate(answer, &scripted.thoughts))
+            .collect::<Vec<_>>(),
+        "usageMetadata": {
+            "promptTokenCount": prompt_tokens,
+            "candidatesTokenCount": output_tokens,
+            "thoughtsTokenCount": thinking_tokens,
+            "totalTokenCount": scripted
+                .total_tokens
+                .unwrap_or(prompt_tokens + output_tokens + thinking_tokens),
+        }
+    })
+}
+
+fn write_response(stream: &mut TcpStream, response: &HttpResponse) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        status_text(response.status),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "INVALID_ARGUMENT",
+        401 => "UNAUTHENTICATED",
+        403 => "PERMISSION_DENIED",
+        404 => "NOT_FOUND",
+        405 => "METHOD_NOT_ALLOWED",
+        429 => "RESOURCE_EXHAUSTED",
+        500 => "INTERNAL",
+        503 => "UNAVAILABLE",
+        _ => "UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FakeServer;
+    use crate::fake_server::rules::FakeRules;
+    use crate::llm::client::{LlmClient, LlmRequest};
+    use crate::llm::config::{HttpConfig, LlmConfig};
+    use crate::llm::gemini::GeminiLlmClient;
+
+    fn client_for(rules: &str, api_key: &str) -> GeminiLlmClient {
+        let rules = FakeRules::from_yaml(rules).expect("rules should parse");
+        let server = FakeServer::bind("127.0.0.1:0", rules).expect("server should bind");
+        let addr = server.local_addr().expect("server address");
+        server.spawn();
+
+        GeminiLlmClient::new(LlmConfig {
+            gemini_api_key: api_key.to_string(),
+            gemini_model: "gemini-2.5-flash".to_string(),
+            gemini_base_url: format!("http://{addr}"),
+            http: HttpConfig::default(),
+        })
+        .expect("client should build")
+    }
+
+    #[test]
+    fn real_client_round_trips_through_fake_server() {
+        let client = client_for(
+            r#"
+rules:
+  - match: { method: generateContent, prompt_contains: commit }
+    response:
+      text: "feat: add parser"
+      thoughts: ["thinking about the diff"]
+  - match: { method: countTokens }
+    response: { total_tokens: 42 }
+"#,
+            "key",
+        );
+
+        let response = client
+            .complete(&LlmRequest::new("", "write a commit message"))
+            .expect("generate should succeed");
+        assert_eq!(response.candidates, vec!["feat: add parser"]);
+        assert_eq!(response.thoughts, vec!["thinking about the diff"]);
+        assert_eq!(client.count_tokens("", "anything").unwrap(), 42);
+    }
+
+    #[test]
+    fn injected_errors_map_to_client_errors() {
+        let client = client_for(
+            r#"
+api_key: expected
+"#,
+            "wrong",
+        );
+        let err = client.generate("", "hi").expect_err("auth should fail");
+        assert!(err.to_string().contains("unauthorized"), "{err}");
+
+        let client = client_for(
+            r#"
+rules:
+  - response: { status: 429, error: quota }
+"#,
+            "key",
+        );
+        let err = client.generate("", "hi").expect_err("quota should fail");
+        assert!(err.to_string().contains("429"), "{err}");
+    }
+}
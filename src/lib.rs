@@ -1,4 +1,7 @@
+pub mod config;
+pub mod fake_server;
 pub mod llm;
+pub mod package;
 pub mod skill;
 pub mod util;
 pub mod workflow;
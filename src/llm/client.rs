@@ -1,5 +1,79 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::llm::tokens::estimate_tokens;
+
+#[derive(Debug, Clone, Default)]
+pub struct LlmRequest {
+    pub model: String,
+    pub prompt: String,
+    pub candidate_count: u32,
+    pub thinking: Option<ThinkingConfig>,
+}
+
+impl LlmRequest {
+    pub fn new(model: &str, prompt: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            candidate_count: 1,
+            thinking: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThinkingConfig {
+    pub budget: Option<i32>,
+    pub include_thoughts: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LlmResponse {
+    pub candidates: Vec<String>,
+    /// Thought summaries, kept apart from the answer text.
+    pub thoughts: Vec<String>,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+    pub thinking_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl Usage {
+    pub fn add(&mut self, other: &Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.output_tokens += other.output_tokens;
+        self.thinking_tokens += other.thinking_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
 
 pub trait LlmClient: Send + Sync {
     fn generate(&self, model: &str, prompt: &str) -> Result<String>;
+
+    /// Providers without native multi-candidate support fall back to repeated calls.
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse> {
+        let candidates = (0..request.candidate_count.max(1))
+            .map(|_| self.generate(&request.model, &request.prompt))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(LlmResponse {
+            candidates,
+            ..Default::default()
+        })
+    }
+
+    /// Providers without a token counting endpoint use the local estimator.
+    fn count_tokens(&self, _model: &str, prompt: &str) -> Result<u64> {
+        Ok(estimate_tokens(prompt))
+    }
+
+    /// One embedding vector per text, in order.
+    fn embed(&self, _model: &str, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Err(anyhow!("This provider does not support embeddings"))
+    }
 }
@@ -1,32 +1,117 @@
-use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::config::ProviderConfig;
 
 #[derive(Debug, Clone)]
 pub struct LlmConfig {
     pub gemini_api_key: String,
     pub gemini_model: String,
     pub gemini_base_url: String,
+    pub http: HttpConfig,
+}
+
+/// HTTP transport settings for a single provider.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub proxy: Option<String>,
+    pub ca_cert: Option<String>,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 120,
+            connect_timeout_secs: 10,
+            proxy: None,
+            ca_cert: None,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Overlays `<PREFIX>_TIMEOUT_SECS`, `<PREFIX>_CONNECT_TIMEOUT_SECS`, `<PREFIX>_PROXY`,
+    /// `<PREFIX>_CA_CERT` and `<PREFIX>_EXTRA_HEADERS` on top of `self`.
+    pub fn with_env(mut self, prefix: &str) -> Result<Self> {
+        if let Ok(value) = std::env::var(format!("{prefix}_TIMEOUT_SECS")) {
+            self.timeout_secs = value
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid {prefix}_TIMEOUT_SECS: {value}"))?;
+        }
+        if let Ok(value) = std::env::var(format!("{prefix}_CONNECT_TIMEOUT_SECS")) {
+            self.connect_timeout_secs = value
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid {prefix}_CONNECT_TIMEOUT_SECS: {value}"))?;
+        }
+        if let Ok(value) = std::env::var(format!("{prefix}_PROXY")) {
+            self.proxy = Some(value);
+        }
+        if let Ok(value) = std::env::var(format!("{prefix}_CA_CERT")) {
+            self.ca_cert = Some(value);
+        }
+        if let Ok(value) = std::env::var(format!("{prefix}_EXTRA_HEADERS")) {
+            self.headers.extend(parse_headers(&value)?);
+        }
+        Ok(self)
+    }
+}
+
+/// Parses `Name: value; Other-Name: value` into header pairs.
+pub fn parse_headers(raw: &str) -> Result<Vec<(String, String)>> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, value) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid header '{entry}', expected 'Name: value'"))?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
 }
 
 impl LlmConfig {
     pub fn from_env() -> Result<Self> {
+        Self::resolve(&ProviderConfig::default())
+    }
+
+    /// Builds the Gemini config from the config file section, with environment overrides.
+    pub fn resolve(file: &ProviderConfig) -> Result<Self> {
         let gemini_api_key = std::env::var("GEMINI_API_KEY")
+            .ok()
+            .or_else(|| file.api_key.clone())
             .context("Missing GEMINI_API_KEY in environment or .env")?;
-        let gemini_model =
-            std::env::var("GEMINI_MODEL").unwrap_or_else(|_| "gemini-3-flash-preview".to_string());
+        let gemini_model = std::env::var("GEMINI_MODEL")
+            .ok()
+            .or_else(|| file.model.clone())
+            .unwrap_or_else(|| "gemini-3-flash-preview".to_string());
         let gemini_base_url = std::env::var("GEMINI_BASE_URL")
-            .unwrap_or_else(|_| "https://generativelanguage.googleapis.com".to_string());
+            .ok()
+            .or_else(|| file.base_url.clone())
+            .unwrap_or_else(|| "https://generativelanguage.googleapis.com".to_string());
+        let http = file.http.clone().with_env("GEMINI")?;
 
         Ok(Self {
             gemini_api_key,
             gemini_model,
             gemini_base_url,
+            http,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::LlmConfig;
+    use super::{parse_headers, LlmConfig};
 
     #[test]
     fn load_config_from_env() {
@@ -41,4 +126,17 @@ mod tests {
         assert_eq!(cfg.gemini_model, "test-model");
         assert_eq!(cfg.gemini_base_url, "https://example.com");
     }
+
+    #[test]
+    fn parses_extra_headers() {
+        let headers = parse_headers("X-Gateway-Key: abc; X-Team:core ;").expect("headers");
+        assert_eq!(
+            headers,
+            vec![
+                ("X-Gateway-Key".to_string(), "abc".to_string()),
+                ("X-Team".to_string(), "core".to_string()),
+            ]
+        );
+        assert!(parse_headers("missing-colon").is_err());
+    }
 }
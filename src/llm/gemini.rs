@@ -1,18 +1,70 @@
-use std::time::Duration;
-
 use anyhow::{anyhow, Context, Result};
 use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error};
 
-use crate::llm::client::LlmClient;
+use crate::llm::client::{LlmClient, LlmRequest, LlmResponse, Usage};
 use crate::llm::config::LlmConfig;
+use crate::llm::http::build_http_client;
 use crate::llm::mock::MockLlmClient;
 
 #[derive(Debug, Serialize)]
 struct GeminiRequest {
     contents: Vec<GeminiContent>,
+    #[serde(rename = "generationConfig", skip_serializing_if = "Option::is_none")]
+    generation_config: Option<GeminiGenerationConfig>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidate_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<GeminiThinkingConfig>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiThinkingConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking_budget: Option<i32>,
+    include_thoughts: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiCountTokensRequest {
+    contents: Vec<GeminiContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCountTokensResponse {
+    #[serde(default)]
+    total_tokens: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiBatchEmbedRequest {
+    requests: Vec<GeminiEmbedRequest>,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiEmbedRequest {
+    model: String,
+    content: GeminiContent,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiBatchEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<GeminiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbedding {
+    values: Vec<f32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -22,12 +74,27 @@ struct GeminiContent {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct GeminiPart {
+    #[serde(default)]
     text: String,
+    #[serde(default, skip_serializing)]
+    thought: bool,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiResponse {
     candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    usage_metadata: GeminiUsageMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct GeminiUsageMetadata {
+    prompt_token_count: u64,
+    candidates_token_count: u64,
+    thoughts_token_count: u64,
+    total_token_count: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,10 +115,7 @@ pub struct GeminiLlmClient {
 
 impl GeminiLlmClient {
     pub fn new(config: LlmConfig) -> Result<Self> {
-        let http = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .context("Failed to build reqwest client")?;
+        let http = build_http_client(&config.http)?;
 
         Ok(Self {
             http,
@@ -60,57 +124,60 @@ impl GeminiLlmClient {
         })
     }
 
-    fn build_request(prompt: &str) -> GeminiRequest {
+    fn build_request(request: &LlmRequest) -> GeminiRequest {
         GeminiRequest {
             contents: vec![GeminiContent {
                 parts: vec![GeminiPart {
-                    text: prompt.to_string(),
+                    text: request.prompt.clone(),
+                    thought: false,
                 }],
             }],
+            generation_config: Self::build_generation_config(request),
         }
     }
 
-    fn parse_response(raw: &str) -> Result<String> {
-        let parsed: GeminiResponse =
-            serde_json::from_str(raw).context("Failed to deserialize Gemini response")?;
+    fn build_generation_config(request: &LlmRequest) -> Option<GeminiGenerationConfig> {
+        let candidate_count = (request.candidate_count > 1).then_some(request.candidate_count);
+        let thinking_config = request.thinking.map(|thinking| GeminiThinkingConfig {
+            thinking_budget: thinking.budget,
+            include_thoughts: thinking.include_thoughts,
+        });
 
-        parsed
-            .candidates
-            .first()
-            .and_then(|candidate| candidate.content.parts.first())
-            .map(|part| part.text.clone())
-            .ok_or_else(|| anyhow!("Gemini response has no candidates/parts/text"))
-    }
-}
-
-impl LlmClient for GeminiLlmClient {
-    fn generate(&self, model: &str, prompt: &str) -> Result<String> {
-        if model == "executor" {
-            debug!("Model executor is configured to fallback to mock response");
-            return self.fallback.generate(model, prompt);
+        if candidate_count.is_none() && thinking_config.is_none() {
+            return None;
         }
+        Some(GeminiGenerationConfig {
+            candidate_count,
+            thinking_config,
+        })
+    }
 
-        let effective_model = if model.trim().is_empty() {
+    fn effective_model<'a>(&'a self, model: &'a str) -> &'a str {
+        if model.trim().is_empty() {
             self.config.gemini_model.as_str()
         } else {
             model
-        };
+        }
+    }
 
-        let request_body = Self::build_request(prompt);
+    /// POSTs to `models/{model}:{method}` and maps auth and non-success statuses to errors.
+    fn post<T: Serialize>(&self, model: &str, method: &str, request_body: &T) -> Result<String> {
+        let effective_model = self.effective_model(model);
         let url = format!(
-            "{}/v1beta/models/{}:generateContent",
+            "{}/v1beta/models/{}:{}",
             self.config.gemini_base_url.trim_end_matches('/'),
-            effective_model
+            effective_model,
+            method
         );
 
-        debug!(model = effective_model, "Sending request to Gemini");
+        debug!(model = effective_model, method, "Sending request to Gemini");
 
         let response = self
             .http
             .post(url)
             .header("x-goog-api-key", &self.config.gemini_api_key)
             .header("Content-Type", "application/json")
-            .json(&request_body)
+            .json(request_body)
             .send()
             .context("Gemini request failed (network/timeout)")?;
 
@@ -131,21 +198,134 @@ impl LlmClient for GeminiLlmClient {
             return Err(anyhow!("Gemini returned non-200 status {status}: {body}"));
         }
 
+        Ok(body)
+    }
+
+    fn parse_response(raw: &str) -> Result<LlmResponse> {
+        let parsed: GeminiResponse =
+            serde_json::from_str(raw).context("Failed to deserialize Gemini response")?;
+
+        let mut candidates = Vec::new();
+        let mut thoughts = Vec::new();
+        for candidate in &parsed.candidates {
+            let (thought_parts, answer_parts): (Vec<_>, Vec<_>) = candidate
+                .content
+                .parts
+                .iter()
+                .partition(|part| part.thought);
+
+            thoughts.extend(thought_parts.iter().map(|part| part.text.clone()));
+            if !answer_parts.is_empty() {
+                candidates.push(answer_parts.iter().map(|part| part.text.as_str()).collect());
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(anyhow!("Gemini response has no candidates/parts/text"));
+        }
+
+        let usage = &parsed.usage_metadata;
+        Ok(LlmResponse {
+            candidates,
+            thoughts,
+            usage: Usage {
+                prompt_tokens: usage.prompt_token_count,
+                output_tokens: usage.candidates_token_count,
+                thinking_tokens: usage.thoughts_token_count,
+                total_tokens: usage.total_token_count,
+            },
+        })
+    }
+}
+
+impl LlmClient for GeminiLlmClient {
+    fn generate(&self, model: &str, prompt: &str) -> Result<String> {
+        let response = self.complete(&LlmRequest::new(model, prompt))?;
+        response
+            .candidates
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Gemini response has no candidates/parts/text"))
+    }
+
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse> {
+        if request.model == "executor" {
+            debug!("Model executor is configured to fallback to mock response");
+            return self.fallback.complete(request);
+        }
+
+        let body = self.post(
+            &request.model,
+            "generateContent",
+            &Self::build_request(request),
+        )?;
         Self::parse_response(&body)
     }
+
+    fn count_tokens(&self, model: &str, prompt: &str) -> Result<u64> {
+        if model == "executor" {
+            return self.fallback.count_tokens(model, prompt);
+        }
+
+        let request = GeminiCountTokensRequest {
+            contents: Self::build_request(&LlmRequest::new(model, prompt)).contents,
+        };
+        let body = self.post(model, "countTokens", &request)?;
+        let parsed: GeminiCountTokensResponse =
+            serde_json::from_str(&body).context("Failed to deserialize countTokens response")?;
+        Ok(parsed.total_tokens)
+    }
+
+    fn embed(&self, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let model = self.effective_model(model);
+        let request = GeminiBatchEmbedRequest {
+            requests: texts
+                .iter()
+                .map(|text| GeminiEmbedRequest {
+                    model: format!("models/{model}"),
+                    content: Self::build_request(&LlmRequest::new(model, text))
+                        .contents
+                        .remove(0),
+                })
+                .collect(),
+        };
+        let body = self.post(model, "batchEmbedContents", &request)?;
+        let parsed: GeminiBatchEmbedResponse = serde_json::from_str(&body)
+            .context("Failed to deserialize batchEmbedContents response")?;
+        if parsed.embeddings.len() != texts.len() {
+            return Err(anyhow!(
+                "Gemini returned {} embeddings for {} texts",
+                parsed.embeddings.len(),
+                texts.len()
+            ));
+        }
+        Ok(parsed.embeddings.into_iter().map(|e| e.values).collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::GeminiLlmClient;
+    use crate::llm::client::{LlmRequest, ThinkingConfig};
 
     #[test]
     fn request_builder_matches_expected_shape() {
-        let request = GeminiLlmClient::build_request("hello");
+        let request = GeminiLlmClient::build_request(&LlmRequest::new("m", "hello"));
         let value = serde_json::to_value(&request).expect("request should be serializable");
 
         assert_eq!(value["contents"][0]["parts"][0]["text"], "hello");
         assert!(value["contents"][0].get("role").is_none());
+        assert!(value.get("generationConfig").is_none());
+    }
+
+    #[test]
+    fn request_builder_sets_candidate_count() {
+        let mut request = LlmRequest::new("m", "hello");
+        request.candidate_count = 3;
+        let value = serde_json::to_value(GeminiLlmClient::build_request(&request))
+            .expect("request should be serializable");
+
+        assert_eq!(value["generationConfig"]["candidateCount"], 3);
     }
 
     #[test]
@@ -163,6 +343,60 @@ mod tests {
         }"#;
 
         let parsed = GeminiLlmClient::parse_response(raw).expect("response should parse");
-        assert_eq!(parsed, "response text");
+        assert_eq!(parsed.candidates, vec!["response text".to_string()]);
+    }
+
+    #[test]
+    fn parser_keeps_every_candidate() {
+        let raw = r#"{
+          "candidates": [
+            { "content": { "parts": [ { "text": "feat: a" } ] } },
+            { "content": { "parts": [ { "text": "fix: " }, { "text": "b" } ] } }
+          ]
+        }"#;
+
+        let parsed = GeminiLlmClient::parse_response(raw).expect("response should parse");
+        assert_eq!(parsed.candidates, vec!["feat: a", "fix: b"]);
+    }
+
+    #[test]
+    fn request_builder_sets_thinking_config() {
+        let mut request = LlmRequest::new("m", "hello");
+        request.thinking = Some(ThinkingConfig {
+            budget: Some(1024),
+            include_thoughts: true,
+        });
+        let value = serde_json::to_value(GeminiLlmClient::build_request(&request))
+            .expect("request should be serializable");
+
+        let thinking = &value["generationConfig"]["thinkingConfig"];
+        assert_eq!(thinking["thinkingBudget"], 1024);
+        assert_eq!(thinking["includeThoughts"], true);
+        assert!(value["generationConfig"].get("candidateCount").is_none());
+        assert!(value["contents"][0]["parts"][0].get("thought").is_none());
+    }
+
+    #[test]
+    fn parser_separates_thoughts_and_counts_thinking_tokens() {
+        let raw = r#"{
+          "candidates": [
+            { "content": { "parts": [
+              { "text": "Looking at the diff...", "thought": true },
+              { "text": "feat: add parser" }
+            ] } }
+          ],
+          "usageMetadata": {
+            "promptTokenCount": 10,
+            "candidatesTokenCount": 4,
+            "thoughtsTokenCount": 25,
+            "totalTokenCount": 39
+          }
+        }"#;
+
+        let parsed = GeminiLlmClient::parse_response(raw).expect("response should parse");
+        assert_eq!(parsed.candidates, vec!["feat: add parser"]);
+        assert_eq!(parsed.thoughts, vec!["Looking at the diff..."]);
+        assert_eq!(parsed.usage.thinking_tokens, 25);
+        assert_eq!(parsed.usage.total_tokens, 39);
     }
 }
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Proxy};
+
+use crate::llm::config::HttpConfig;
+
+/// Builds a blocking client honouring timeouts, proxy, extra CA bundle and default headers.
+pub fn build_http_client(config: &HttpConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs));
+
+    if let Some(proxy) = &config.proxy {
+        builder =
+            builder.proxy(Proxy::all(proxy).with_context(|| format!("Invalid proxy {proxy}"))?);
+    }
+
+    if let Some(path) = &config.ca_cert {
+        let pem =
+            std::fs::read(path).with_context(|| format!("Failed to read CA bundle {path}"))?;
+        for cert in Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid PEM CA bundle {path}"))?
+        {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("Invalid header name {name}"))?;
+        let value = HeaderValue::from_str(value)
+            .with_context(|| format!("Invalid value for header {name}"))?;
+        headers.insert(name, value);
+    }
+
+    builder
+        .default_headers(headers)
+        .build()
+        .context("Failed to build reqwest client")
+}
@@ -1,7 +1,13 @@
 use anyhow::Result;
+use sha2::{Digest, Sha256};
 
 use crate::llm::client::LlmClient;
+use crate::skill::lexical::tokenize;
 
+/// Dimensions of [`hashed_embedding`] vectors.
+const MOCK_EMBEDDING_DIMS: usize = 64;
+
+#[derive(Default)]
 pub struct MockLlmClient;
 
 impl MockLlmClient {
@@ -30,4 +36,22 @@ impl LlmClient for MockLlmClient {
 
         Ok(format!("[mock:{model}] {prompt}"))
     }
+
+    fn embed(&self, _model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| hashed_embedding(text)).collect())
+    }
+}
+
+/// Deterministic bag-of-words vector: texts sharing words point the same way.
+pub fn hashed_embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; MOCK_EMBEDDING_DIMS];
+    for token in tokenize(text) {
+        let digest = Sha256::digest(token.as_bytes());
+        vector[digest[0] as usize % MOCK_EMBEDDING_DIMS] += 1.0;
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
 }
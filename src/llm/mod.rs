@@ -1,5 +1,8 @@
 pub mod client;
 pub mod config;
 pub mod gemini;
+pub mod http;
 pub mod mock;
+pub mod models;
 pub mod prompt;
+pub mod tokens;
@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::RequestBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GenAiConfig;
+use crate::llm::config::{HttpConfig, LlmConfig};
+use crate::llm::http::build_http_client;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Gemini,
+    OpenAi,
+    Ollama,
+}
+
+impl ProviderKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Gemini => "gemini",
+            ProviderKind::OpenAi => "openai",
+            ProviderKind::Ollama => "ollama",
+        }
+    }
+}
+
+/// A provider that has enough configuration to be queried.
+#[derive(Debug, Clone)]
+pub struct ProviderEndpoint {
+    pub kind: ProviderKind,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub http: HttpConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelInfo {
+    pub provider: ProviderKind,
+    pub name: String,
+    pub input_token_limit: Option<u64>,
+    pub output_token_limit: Option<u64>,
+}
+
+/// Gemini is configured by an API key, OpenAI-compatible servers by `OPENAI_BASE_URL` or
+/// `OPENAI_API_KEY`, and Ollama by `OLLAMA_HOST`; the config file can supply the same values.
+pub fn configured_providers(config: &GenAiConfig) -> Result<Vec<ProviderEndpoint>> {
+    let mut providers = Vec::new();
+
+    if let Ok(gemini) = LlmConfig::resolve(&config.providers.gemini) {
+        providers.push(ProviderEndpoint {
+            kind: ProviderKind::Gemini,
+            base_url: gemini.gemini_base_url,
+            api_key: Some(gemini.gemini_api_key),
+            http: gemini.http,
+        });
+    }
+
+    let openai = &config.providers.openai;
+    let openai_key = env_or("OPENAI_API_KEY", &openai.api_key);
+    let openai_url = env_or("OPENAI_BASE_URL", &openai.base_url);
+    if openai_key.is_some() || openai_url.is_some() {
+        providers.push(ProviderEndpoint {
+            kind: ProviderKind::OpenAi,
+            base_url: openai_url.unwrap_or_else(|| "https://api.openai.com".to_string()),
+            api_key: openai_key,
+            http: openai.http.clone().with_env("OPENAI")?,
+        });
+    }
+
+    let ollama = &config.providers.ollama;
+    if let Some(host) = env_or("OLLAMA_HOST", &ollama.base_url) {
+        providers.push(ProviderEndpoint {
+            kind: ProviderKind::Ollama,
+            base_url: normalize_ollama_host(&host),
+            api_key: None,
+            http: ollama.http.clone().with_env("OLLAMA")?,
+        });
+    }
+
+    Ok(providers)
+}
+
+fn env_or(var: &str, fallback: &Option<String>) -> Option<String> {
+    std::env::var(var).ok().or_else(|| fallback.clone())
+}
+
+fn normalize_ollama_host(host: &str) -> String {
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else {
+        format!("http://{host}")
+    }
+}
+
+pub fn list_models(endpoint: &ProviderEndpoint) -> Result<Vec<ModelInfo>> {
+    let http = build_http_client(&endpoint.http)?;
+    let base = endpoint.base_url.trim_end_matches('/');
+
+    match endpoint.kind {
+        ProviderKind::Gemini => {
+            let mut models = Vec::new();
+            let mut page_token: Option<String> = None;
+            loop {
+                let mut request = http.get(format!("{base}/v1beta/models"));
+                if let Some(token) = &page_token {
+                    request = request.query(&[("pageToken", token)]);
+                }
+                if let Some(key) = &endpoint.api_key {
+                    request = request.header("x-goog-api-key", key);
+                }
+                let (page, next) = parse_gemini_models(&fetch(request, endpoint.kind)?)?;
+                models.extend(page);
+                match next {
+                    Some(token) if !token.is_empty() => page_token = Some(token),
+                    _ => break,
+                }
+            }
+            Ok(models)
+        }
+        ProviderKind::OpenAi => {
+            let mut request = http.get(format!("{base}/v1/models"));
+            if let Some(key) = &endpoint.api_key {
+                request = request.bearer_auth(key);
+            }
+            parse_openai_models(&fetch(request, endpoint.kind)?)
+        }
+        ProviderKind::Ollama => {
+            parse_ollama_models(&fetch(http.get(format!("{base}/api/tags")), endpoint.kind)?)
+        }
+    }
+}
+
+fn fetch(request: RequestBuilder, kind: ProviderKind) -> Result<String> {
+    let response = request
+        .send()
+        .with_context(|| format!("{} model listing failed (network/timeout)", kind.as_str()))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .context("Failed to read model listing body")?;
+    if !status.is_success() {
+        return Err(anyhow!(
+            "{} returned non-200 status {status}: {body}",
+            kind.as_str()
+        ));
+    }
+    Ok(body)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModelList {
+    #[serde(default)]
+    models: Vec<GeminiModel>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModel {
+    name: String,
+    input_token_limit: Option<u64>,
+    output_token_limit: Option<u64>,
+}
+
+fn parse_gemini_models(raw: &str) -> Result<(Vec<ModelInfo>, Option<String>)> {
+    let parsed: GeminiModelList =
+        serde_json::from_str(raw).context("Failed to deserialize Gemini model list")?;
+    let models = parsed
+        .models
+        .into_iter()
+        .map(|model| ModelInfo {
+            provider: ProviderKind::Gemini,
+            name: model
+                .name
+                .strip_prefix("models/")
+                .unwrap_or(&model.name)
+                .to_string(),
+            input_token_limit: model.input_token_limit,
+            output_token_limit: model.output_token_limit,
+        })
+        .collect();
+    Ok((models, parsed.next_page_token))
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModelList {
+    #[serde(default)]
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiModel {
+    id: String,
+    #[serde(alias = "context_length", alias = "max_model_len")]
+    context_window: Option<u64>,
+}
+
+fn parse_openai_models(raw: &str) -> Result<Vec<ModelInfo>> {
+    let parsed: OpenAiModelList =
+        serde_json::from_str(raw).context("Failed to deserialize OpenAI model list")?;
+    Ok(parsed
+        .data
+        .into_iter()
+        .map(|model| ModelInfo {
+            provider: ProviderKind::OpenAi,
+            name: model.id,
+            input_token_limit: model.context_window,
+            output_token_limit: None,
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModelList {
+    #[serde(default)]
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+fn parse_ollama_models(raw: &str) -> Result<Vec<ModelInfo>> {
+    let parsed: OllamaModelList =
+        serde_json::from_str(raw).context("Failed to deserialize Ollama model list")?;
+    Ok(parsed
+        .models
+        .into_iter()
+        .map(|model| ModelInfo {
+            provider: ProviderKind::Ollama,
+            name: model.name,
+            input_token_limit: None,
+            output_token_limit: None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_gemini_models, parse_ollama_models, parse_openai_models};
+
+    #[test]
+    fn parses_gemini_models_with_limits_and_page_token() {
+        let raw = r#"{
+          "models": [
+            { "name": "models/gemini-2.5-flash", "inputTokenLimit": 1048576, "outputTokenLimit": 65536 }
+          ],
+          "nextPageToken": "abc"
+        }"#;
+        let (models, next) = parse_gemini_models(raw).expect("gemini list should parse");
+        assert_eq!(models[0].name, "gemini-2.5-flash");
+        assert_eq!(models[0].input_token_limit, Some(1048576));
+        assert_eq!(next.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn parses_openai_and_ollama_models() {
+        let openai = parse_openai_models(r#"{"data":[{"id":"gpt-4o","context_length":128000}]}"#)
+            .expect("openai list should parse");
+        assert_eq!(openai[0].name, "gpt-4o");
+        assert_eq!(openai[0].input_token_limit, Some(128000));
+
+        let ollama = parse_ollama_models(r#"{"models":[{"name":"llama3:8b","size":1}]}"#)
+            .expect("ollama list should parse");
+        assert_eq!(ollama[0].name, "llama3:8b");
+    }
+}
@@ -1,12 +1,15 @@
 use crate::skill::model::Skill;
 
-pub fn build_selector_prompt(user_input: &str, skills: &[Skill]) -> String {
+pub fn build_selector_prompt(user_input: &str, skills: &[&Skill], top_k: usize) -> String {
     let skills_text = skills
         .iter()
         .map(|s| {
             format!(
                 "- name: {}\n  description: {}\n  category: {}\n  tags: {:?}",
-                s.metadata.name, s.metadata.description, s.metadata.category, s.metadata.tags
+                s.qualified_name(),
+                s.metadata.description,
+                s.metadata.category,
+                s.metadata.tags
             )
         })
         .collect::<Vec<_>>()
@@ -14,7 +17,41 @@ pub fn build_selector_prompt(user_input: &str, skills: &[Skill]) -> String {
 
     format!(
         "Select best skill for user request. Return strict JSON: \
-{{\"skill\":\"...\",\"confidence\":0.0,\"reason\":\"...\"}}\n\
+{{\"skill\":\"...\",\"confidence\":0.0,\"reason\":\"...\",\
+\"alternatives\":[{{\"skill\":\"...\",\"confidence\":0.0,\"reason\":\"...\"}}]}}\n\
+Confidence is between 0 and 1. List up to {} next-best skills in alternatives.\n\
+User input: {user_input}\nAvailable skills:\n{skills_text}",
+        top_k.saturating_sub(1)
+    )
+}
+
+pub fn build_planner_prompt(user_input: &str, skills: &[Skill], max_steps: usize) -> String {
+    let skills_text = skills
+        .iter()
+        .map(|s| {
+            let inputs = s
+                .metadata
+                .inputs
+                .iter()
+                .map(|input| format!("{} ({})", input.name, input.input_type.as_str()))
+                .collect::<Vec<_>>();
+            format!(
+                "- name: {}\n  description: {}\n  inputs: {:?}",
+                s.qualified_name(),
+                s.metadata.description,
+                inputs
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "Plan which skills to run, in order, to fulfil the user request. Use only the \
+available skills, at most {max_steps} steps, and only their declared inputs. Return strict JSON: \
+{{\"steps\":[{{\"skill\":\"...\",\"reason\":\"...\",\"prompt\":\"...\",\"inputs\":{{}}}}]}}\n\
+\"prompt\" and input values may use {{{{user_input}}}}, {{{{previous_output}}}} and \
+{{{{stepN_output}}}} (N starting at 1) to pass earlier results on. Omit \"prompt\" to use the \
+request, plus the previous step's output after the first step.\n\
 User input: {user_input}\nAvailable skills:\n{skills_text}"
     )
 }
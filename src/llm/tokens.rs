@@ -0,0 +1,20 @@
+/// Rough offline token estimate: about four characters per token, counting words
+/// as at least one token so short prompts are not underestimated.
+pub fn estimate_tokens(text: &str) -> u64 {
+    let chars = text.chars().count() as u64;
+    let words = text.split_whitespace().count() as u64;
+    chars.div_ceil(4).max(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::estimate_tokens;
+
+    #[test]
+    fn estimates_roughly_four_chars_per_token() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("a b c d e f"), 6);
+    }
+}
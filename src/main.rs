@@ -1,21 +1,57 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use genai::config::GenAiConfig;
+use genai::fake_server::rules::FakeRules;
+use genai::fake_server::server::FakeServer;
 use genai::llm::client::LlmClient;
 use genai::llm::config::LlmConfig;
 use genai::llm::gemini::GeminiLlmClient;
 use genai::llm::mock::MockLlmClient;
-use genai::skill::scanner::scan_skills;
+use genai::llm::models::{configured_providers, list_models};
+use genai::llm::tokens::estimate_tokens;
+use genai::package::archive::{collect_package, write_archive};
+use genai::package::install::{
+    install, install_locked, pinned_by, uninstall, InstallTarget, PackageSource,
+};
+use genai::package::lock::LOCK_FILE;
+use genai::package::registry::RegistryIndex;
+use genai::package::signing::{
+    generate_keypair, sign_skill, verify_skill, TrustConfig, SIGNATURE_FILE,
+};
+use genai::skill::catalog::{find_skill, latest_versions, SkillCatalog};
+use genai::skill::diagnostics::{check_skill_file, render_diagnostics, Severity};
+use genai::skill::eval::{
+    evaluate, load_dataset, render_json as render_eval_json, render_text as render_eval_text,
+    Strategy,
+};
+use genai::skill::format::{convert, find_skill_file, is_skill_file, SkillFormat};
+use genai::skill::inputs::{parse_assignments, read_input_file, resolve_inputs, InputPrompt};
+use genai::skill::lint::{fix_skill_file, lint_skill_file, RULES};
+use genai::skill::manifest::ProjectManifest;
+use genai::skill::model::{InputSpec, Skill};
+use genai::skill::parser::render_resolved;
+use genai::skill::planner::{plan_skills, render_plan};
+use genai::skill::roots::resolve_skill_roots;
 use genai::skill::selector::select_skill;
-use genai::skill::validator::validate_skill;
-use genai::workflow::executor::{ExecutionInput, WorkflowExecutor};
+use genai::skill::validator::{dataflow_warnings, unknown_model_warnings};
+use genai::workflow::compare::{compare_models, render_json, render_table};
+use genai::workflow::dry_run::render_llm_prompts;
+use genai::workflow::executor::{ExecutionInput, ExecutionOptions, WorkflowExecutor};
+use genai::workflow::plan::execute_plan;
 use tracing::{debug, info, warn};
 
 #[derive(Parser, Debug)]
 #[command(name = "genai")]
 #[command(about = "Skill-based Rust agent runtime")]
 struct Cli {
+    /// Skill directory searched before the default roots; repeatable
     #[arg(long)]
-    skills_dir: Option<String>,
+    skills_dir: Vec<String>,
 
     #[arg(long, default_value_t = false)]
     debug: bool,
@@ -30,8 +66,182 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     List,
-    Run { prompt: String },
-    RunSkill { skill_name: String, prompt: String },
+    Run {
+        prompt: String,
+        /// Print the chosen skill, confidence, reason and runner-ups to stderr
+        #[arg(long, default_value_t = false)]
+        explain: bool,
+        /// Plan an ordered sequence of skills for a compound request
+        #[arg(long, default_value_t = false)]
+        plan: bool,
+        /// Run the plan without asking for confirmation
+        #[arg(long, default_value_t = false, requires = "plan")]
+        yes: bool,
+        #[command(flatten)]
+        inputs: InputArgs,
+    },
+    RunSkill {
+        skill_name: String,
+        prompt: String,
+        #[command(flatten)]
+        inputs: InputArgs,
+    },
+    /// Print a skill's SKILL.md
+    Show {
+        skill_name: String,
+        /// Print the workflow with extends and include applied
+        #[arg(long, default_value_t = false)]
+        resolved: bool,
+    },
+    /// Package a skill as <name>-<version>.tar.gz with a content checksum
+    Pack {
+        skill_name: String,
+        #[arg(long, default_value = ".")]
+        out: String,
+        /// Also add the archive to <out>/index.yaml, making <out> a registry
+        #[arg(long, default_value_t = false)]
+        index: bool,
+    },
+    /// Install from an archive, skill directory or registry; with no source, install
+    /// everything in genai.lock
+    Install {
+        source: Option<String>,
+        /// Skill to take from a registry source, as name[@requirement]
+        skill: Option<String>,
+        /// Install into ~/GenAI/skills instead of the project
+        #[arg(long, default_value_t = false)]
+        global: bool,
+    },
+    /// Remove installed versions of a skill and their lock entries
+    Uninstall {
+        /// name[@requirement]
+        skill: String,
+        #[arg(long, default_value_t = false)]
+        global: bool,
+    },
+    /// Generate an ed25519 key pair: <path> (private) and <path>.pub
+    Keygen {
+        path: String,
+    },
+    /// Sign a skill, writing SKILL.sig next to its SKILL.md
+    Sign {
+        skill_name: String,
+        #[arg(long)]
+        key: String,
+    },
+    /// Check skill signatures against the trusted keys; all skills when none is named
+    Verify {
+        skill_name: Option<String>,
+    },
+    /// List models offered by each configured provider
+    Models {
+        /// Warn about skills referencing models no provider lists
+        #[arg(long, default_value_t = false)]
+        check: bool,
+    },
+    /// Render every llm step of a skill and report its token count
+    ///
+    /// Command steps run for real to fill in the prompts, so skills with write_access
+    /// are refused unless --run-commands is given.
+    Tokens {
+        skill_name: String,
+        prompt: String,
+        /// Use the local estimator instead of the provider's countTokens endpoint
+        #[arg(long, default_value_t = false)]
+        local: bool,
+        /// Allow skills with write_access; their command steps may change files
+        #[arg(long, default_value_t = false)]
+        run_commands: bool,
+        #[command(flatten)]
+        inputs: InputArgs,
+    },
+    /// Run the same skill once per model and show the results side by side
+    Compare {
+        #[arg(long, value_delimiter = ',', required = true)]
+        models: Vec<String>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        #[command(subcommand)]
+        target: CompareTarget,
+    },
+    /// Route a JSONL dataset of {"prompt", "expected"} lines and report selector accuracy
+    EvalSelector {
+        dataset: String,
+        #[arg(long, value_delimiter = ',', default_value = "llm,lexical,semantic")]
+        strategies: Vec<Strategy>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// Fail when any strategy scores below this accuracy (0.0-1.0)
+        #[arg(long)]
+        min_accuracy: Option<f64>,
+    },
+    /// Report every problem in skill files with its line and column; checks all
+    /// skill roots when no path is given
+    Check {
+        /// Skill files or directories to search for them
+        paths: Vec<String>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Report style problems in skill files; rule levels come from `lint.rules` in
+    /// the config file
+    Lint {
+        /// Skill files or directories to search for them
+        paths: Vec<String>,
+        /// Apply mechanical fixes in place before reporting
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+        /// List the rules with their default levels and exit
+        #[arg(long, default_value_t = false)]
+        rules: bool,
+    },
+    /// Rewrite a skill in another format, failing if anything would be lost
+    Convert {
+        /// Skill file, or the directory holding it
+        path: String,
+        /// md, md-toml, yaml or toml
+        #[arg(long)]
+        to: SkillFormat,
+        /// Replace the skill file instead of printing the result
+        #[arg(long, default_value_t = false)]
+        write: bool,
+    },
+    /// Serve a scripted fake of the Gemini REST API for offline development
+    FakeServer {
+        #[arg(long, default_value = "127.0.0.1:8089")]
+        addr: String,
+        /// YAML rules file; without one every prompt is echoed back
+        #[arg(long)]
+        rules: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CompareTarget {
+    RunSkill {
+        skill_name: String,
+        prompt: String,
+        #[command(flatten)]
+        inputs: InputArgs,
+    },
+}
+
+#[derive(Args, Debug)]
+struct InputArgs {
+    /// Set a declared skill input; repeatable
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
+    /// YAML or JSON mapping of input values; --set takes precedence
+    #[arg(long)]
+    input_file: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
 }
 
 fn init_tracing(debug_mode: bool) {
@@ -55,12 +265,13 @@ fn init_env() {
     }
 }
 
-fn build_llm_client(real_llm: bool) -> Box<dyn LlmClient> {
-    let has_key = std::env::var("GEMINI_API_KEY").is_ok();
+fn build_llm_client(config: &GenAiConfig, real_llm: bool) -> Box<dyn LlmClient> {
+    let gemini = &config.providers.gemini;
+    let has_key = std::env::var("GEMINI_API_KEY").is_ok() || gemini.api_key.is_some();
     let should_use_real = real_llm || has_key;
 
     if should_use_real {
-        match LlmConfig::from_env().and_then(GeminiLlmClient::new) {
+        match LlmConfig::resolve(gemini).and_then(GeminiLlmClient::new) {
             Ok(client) => {
                 info!("Using GeminiLlmClient");
                 return Box::new(client);
@@ -75,82 +286,714 @@ fn build_llm_client(real_llm: bool) -> Box<dyn LlmClient> {
     Box::new(MockLlmClient::new())
 }
 
-fn resolve_skills_dir(cli: Option<String>) -> Result<String> {
-    if let Some(dir) = cli {
-        return Ok(dir);
-    }
-
-    if let Ok(dir) = std::env::var("GENAI_SKILLS_DIR") {
-        return Ok(dir);
-    }
-
-    let home = std::env::var("HOME").map_err(|_| anyhow::anyhow!("HOME env not set"))?;
-
-    let default = format!("{home}/GenAI/skills");
-    if std::path::Path::new(&default).exists() {
-        return Ok(default);
-    }
-
-    Err(anyhow::anyhow!(
-        "Skills directory not found. Use --skills-dir or set GENAI_SKILLS_DIR"
-    ))
-}
-
 fn main() -> Result<()> {
     init_env();
 
     let cli = Cli::parse();
     init_tracing(cli.debug);
 
-    let skills_dir = resolve_skills_dir(cli.skills_dir)?;
-
-    let skills = scan_skills(&skills_dir)?;
-    for skill in &skills {
-        validate_skill(skill)?;
-    }
+    let config = GenAiConfig::load()?;
 
     match cli.command {
         Commands::List => {
-            for skill in &skills {
+            let catalog = load_catalog(&cli.skills_dir, &config.trust)?;
+            let mut entries = catalog.entries.iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| {
+                a.skill
+                    .qualified_name()
+                    .cmp(&b.skill.qualified_name())
+                    .then(
+                        b.skill
+                            .metadata
+                            .semver()
+                            .ok()
+                            .cmp(&a.skill.metadata.semver().ok()),
+                    )
+            });
+            for entry in entries {
+                let skill = &entry.skill;
+                let shadowed = match (&entry.shadowed_by, &entry.excluded_by_pin) {
+                    (Some(path), _) => format!(" (shadowed by {path})"),
+                    (None, Some(pin)) => format!(" (excluded by pin {pin})"),
+                    (None, None) => String::new(),
+                };
                 println!(
-                    "{} ({}) - {}",
-                    skill.metadata.name, skill.metadata.version, skill.metadata.description
+                    "{} ({}) - {} [{}: {}]{}",
+                    skill.qualified_name(),
+                    skill.metadata.version,
+                    skill.metadata.description,
+                    entry.root.kind.as_str(),
+                    skill.path,
+                    shadowed
                 );
             }
         }
-        Commands::Run { prompt } => {
-            let selector_llm = build_llm_client(cli.real_llm);
-            let selected = select_skill(&prompt, &skills, Some(selector_llm.as_ref()))?;
+        Commands::Run {
+            prompt,
+            plan: true,
+            yes,
+            inputs,
+            ..
+        } => {
+            if !inputs.set.is_empty() || inputs.input_file.is_some() {
+                return Err(anyhow::anyhow!(
+                    "--set and --input-file cannot be combined with --plan; the plan sets each skill's inputs"
+                ));
+            }
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let candidates = latest_versions(&skills);
+            let interactive = std::io::stdin().is_terminal();
+            let plan = plan_skills(
+                &prompt,
+                &candidates,
+                build_llm_client(&config, cli.real_llm).as_ref(),
+                &config.selector,
+            )?;
+            eprintln!("Plan:\n{}", render_plan(&plan));
+            if !yes && !confirm("Run this plan?", interactive)? {
+                return Err(anyhow::anyhow!(
+                    "Plan not confirmed{}",
+                    if interactive {
+                        ""
+                    } else {
+                        "; pass --yes to run it non-interactively"
+                    }
+                ));
+            }
+
+            let mut executor = WorkflowExecutor::with_options(
+                build_llm_client(&config, cli.real_llm),
+                execution_options(&skills),
+            );
+            let results = execute_plan(&plan, &prompt, &mut executor, cli.debug, interactive)?;
+            if let Some((last, earlier)) = results.split_last() {
+                for result in earlier {
+                    debug!("Output of {}:\n{}", result.skill, result.output);
+                }
+                println!("{}", last.output);
+            }
+        }
+        Commands::Run {
+            prompt,
+            explain,
+            plan: false,
+            inputs,
+            ..
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let selector_llm = build_llm_client(&config, cli.real_llm);
+            let candidates = latest_versions(&skills);
+            let interactive = std::io::stdin().is_terminal();
+            let selection = select_skill(
+                &prompt,
+                &candidates,
+                Some(selector_llm.as_ref()),
+                &config.selector,
+                interactive,
+            )?;
+            if explain {
+                eprintln!("{}", selection.explain());
+            }
+            let selected = selection.skill;
             info!("Selected skill: {}", selected.metadata.name);
+            let inputs = resolve_skill_inputs(selected, &inputs, interactive)?;
 
-            let mut executor = WorkflowExecutor::new(build_llm_client(cli.real_llm));
+            let mut executor = WorkflowExecutor::with_options(
+                build_llm_client(&config, cli.real_llm),
+                execution_options(&skills),
+            );
             let result = executor.execute(
                 selected,
                 ExecutionInput {
                     user_prompt: prompt,
                     debug: cli.debug,
+                    interactive,
+                    inputs,
                 },
             )?;
+            debug!(
+                "Execution trace:\n{}",
+                serde_json::to_string_pretty(executor.trace())?
+            );
             println!("{result}");
         }
-        Commands::RunSkill { skill_name, prompt } => {
-            let skill = skills
-                .iter()
-                .find(|s| s.metadata.name == skill_name)
-                .ok_or_else(|| anyhow::anyhow!("Skill not found: {skill_name}"))?;
+        Commands::RunSkill {
+            skill_name,
+            prompt,
+            inputs,
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let skill = find_skill(&skills, &skill_name)?;
+            let interactive = std::io::stdin().is_terminal();
+            let inputs = resolve_skill_inputs(skill, &inputs, interactive)?;
 
             debug!("Running skill: {}", skill.metadata.name);
-            let mut executor = WorkflowExecutor::new(build_llm_client(cli.real_llm));
+            let mut executor = WorkflowExecutor::with_options(
+                build_llm_client(&config, cli.real_llm),
+                execution_options(&skills),
+            );
             let result = executor.execute(
                 skill,
                 ExecutionInput {
                     user_prompt: prompt,
                     debug: cli.debug,
+                    interactive,
+                    inputs,
                 },
             )?;
+            debug!(
+                "Execution trace:\n{}",
+                serde_json::to_string_pretty(executor.trace())?
+            );
             println!("{result}");
         }
+        Commands::Show {
+            skill_name,
+            resolved,
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let skill = find_skill(&skills, &skill_name)?;
+            if resolved {
+                print!("{}", render_resolved(skill)?);
+            } else {
+                print!("{}", std::fs::read_to_string(&skill.path)?);
+            }
+        }
+        Commands::Pack {
+            skill_name,
+            out,
+            index,
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let skill = find_skill(&skills, &skill_name)?;
+            let (info, files) = collect_package(skill)?;
+            let out = Path::new(&out);
+            let archive = write_archive(&info, &files, out)?;
+            if index {
+                let mut registry = RegistryIndex::load(out)?;
+                registry.publish(info.clone(), info.archive_name());
+                registry.save(out)?;
+            }
+            println!("{} {}", archive.display(), info.checksum);
+        }
+        Commands::Install {
+            source,
+            skill,
+            global,
+        } => {
+            let cwd = std::env::current_dir()?;
+            let target = install_target(&cwd, global)?;
+            match source {
+                Some(source) => {
+                    let registry = ProjectManifest::discover(&cwd)?.and_then(|(path, manifest)| {
+                        manifest
+                            .registry
+                            .map(|dir| path.parent().unwrap_or(Path::new(".")).join(dir))
+                    });
+                    let source =
+                        PackageSource::from_arg(&source, skill.as_deref(), registry.as_deref())?;
+                    let locked = install(&target, &source)?;
+                    println!(
+                        "Installed {}@{} ({})",
+                        locked.name, locked.version, locked.checksum
+                    );
+                }
+                None => {
+                    for locked in install_locked(&target)? {
+                        println!(
+                            "Installed {}@{} ({})",
+                            locked.name, locked.version, locked.checksum
+                        );
+                    }
+                }
+            }
+        }
+        Commands::Uninstall { skill, global } => {
+            let target = install_target(&std::env::current_dir()?, global)?;
+            for locked in uninstall(&target, &skill)? {
+                println!("Removed {}@{}", locked.name, locked.version);
+            }
+        }
+        Commands::Keygen { path } => {
+            let (private_key, public_key) = generate_keypair();
+            write_private_key(Path::new(&path), &private_key)?;
+            std::fs::write(format!("{path}.pub"), format!("{public_key}\n"))?;
+            println!("{public_key}");
+        }
+        Commands::Sign { skill_name, key } => {
+            let skills = load_skills(&cli.skills_dir, &TrustConfig::default())?;
+            let skill = find_skill(&skills, &skill_name)?;
+            let private_key = std::fs::read_to_string(&key)?;
+            let signed = sign_skill(skill, &private_key)?;
+            println!("Signed {} ({})", skill.qualified_name(), signed.checksum);
+        }
+        Commands::Verify { skill_name } => {
+            let skills = load_skills(&cli.skills_dir, &TrustConfig::default())?;
+            let targets = match &skill_name {
+                Some(name) => vec![find_skill(&skills, name)?],
+                None => skills.iter().collect(),
+            };
+            let mut failures = 0;
+            for skill in targets {
+                match verify_skill(skill, &config.trust) {
+                    Ok(key) => println!("OK   {} (signed by {key})", skill.qualified_name()),
+                    Err(err) => {
+                        failures += 1;
+                        println!("FAIL {}: {err}", skill.qualified_name());
+                    }
+                }
+            }
+            if failures > 0 {
+                return Err(anyhow::anyhow!("{failures} skill(s) failed verification"));
+            }
+        }
+        Commands::Models { check } => {
+            let mut known = Vec::new();
+            for provider in configured_providers(&config)? {
+                match list_models(&provider) {
+                    Ok(models) => {
+                        for model in models {
+                            println!(
+                                "{:<8} {:<48} in={:<10} out={}",
+                                provider.kind.as_str(),
+                                model.name,
+                                format_limit(model.input_token_limit),
+                                format_limit(model.output_token_limit)
+                            );
+                            known.push(model.name);
+                        }
+                    }
+                    Err(err) => warn!("Unable to list {} models: {err}", provider.kind.as_str()),
+                }
+            }
+
+            if check {
+                for skill in &load_skills(&cli.skills_dir, &config.trust)? {
+                    for warning in unknown_model_warnings(skill, &known) {
+                        warn!("{warning}");
+                    }
+                }
+            }
+        }
+        Commands::Tokens {
+            skill_name,
+            prompt,
+            local,
+            run_commands,
+            inputs,
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let skill = find_skill(&skills, &skill_name)?;
+            if skill.metadata.permissions.write_access && !run_commands {
+                return Err(anyhow::anyhow!(
+                    "Skill '{}' has write_access and tokens runs its command steps; pass --run-commands to allow it",
+                    skill.metadata.name
+                ));
+            }
+            let inputs = resolve_skill_inputs(skill, &inputs, false)?;
+            let counter = build_llm_client(&config, cli.real_llm);
+
+            let rendered = render_llm_prompts(
+                skill,
+                ExecutionInput {
+                    user_prompt: prompt,
+                    debug: cli.debug,
+                    interactive: false,
+                    inputs,
+                },
+                execution_options(&skills),
+            )?;
+            for step in rendered {
+                let tokens = if local {
+                    estimate_tokens(&step.prompt)
+                } else {
+                    counter
+                        .count_tokens(&step.model, &step.prompt)
+                        .unwrap_or_else(|err| {
+                            warn!(
+                                "countTokens failed for '{}', estimating: {err}",
+                                step.step_id
+                            );
+                            estimate_tokens(&step.prompt)
+                        })
+                };
+                println!("{:<32} {:<32} {tokens}", step.step_id, step.model);
+            }
+        }
+        Commands::Compare {
+            models,
+            format,
+            target:
+                CompareTarget::RunSkill {
+                    skill_name,
+                    prompt,
+                    inputs,
+                },
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let skill = find_skill(&skills, &skill_name)?;
+            if !skill.metadata.permissions.network_access {
+                return Err(anyhow::anyhow!(
+                    "{} has network_access: false, so its steps cannot be run on other models",
+                    skill.qualified_name()
+                ));
+            }
+            let input = ExecutionInput {
+                user_prompt: prompt,
+                debug: cli.debug,
+                interactive: false,
+                inputs: resolve_skill_inputs(skill, &inputs, false)?,
+            };
+
+            let factory = || build_llm_client(&config, cli.real_llm);
+            let results = compare_models(
+                skill,
+                &models,
+                &input,
+                &execution_options(&skills),
+                &factory,
+            );
+            match format {
+                OutputFormat::Table => println!("{}", render_table(&results)),
+                OutputFormat::Json => println!("{}", render_json(&results)?),
+            }
+        }
+        Commands::EvalSelector {
+            dataset,
+            strategies,
+            format,
+            min_accuracy,
+        } => {
+            let skills = load_skills(&cli.skills_dir, &config.trust)?;
+            let candidates = latest_versions(&skills);
+            let cases = load_dataset(Path::new(&dataset))?;
+            let llm = build_llm_client(&config, cli.real_llm);
+            let reports = evaluate(
+                &cases,
+                &candidates,
+                llm.as_ref(),
+                &config.selector,
+                &strategies,
+            )?;
+            match format {
+                OutputFormat::Table => println!("{}", render_eval_text(&reports)),
+                OutputFormat::Json => println!("{}", render_eval_json(&reports)?),
+            }
+
+            if let Some(min) = min_accuracy {
+                let below = reports
+                    .iter()
+                    .filter(|report| report.accuracy < min)
+                    .map(|report| format!("{} {:.2}", report.strategy.as_str(), report.accuracy))
+                    .collect::<Vec<_>>();
+                if !below.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "Selector accuracy below {min:.2}: {}",
+                        below.join(", ")
+                    ));
+                }
+            }
+        }
+        Commands::Check { paths, format } => {
+            let files = skill_files(&paths, &cli.skills_dir)?;
+
+            let diagnostics = files
+                .iter()
+                .flat_map(|file| check_skill_file(file))
+                .collect::<Vec<_>>();
+            match format {
+                OutputFormat::Table => println!("{}", render_diagnostics(&diagnostics)),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diagnostics)?),
+            }
+
+            let failed = diagnostics
+                .iter()
+                .filter(|d| d.severity == Severity::Error)
+                .filter_map(|d| d.file.as_deref())
+                .collect::<std::collections::BTreeSet<_>>();
+            if !failed.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "{} of {} skill file(s) have errors",
+                    failed.len(),
+                    files.len()
+                ));
+            }
+        }
+        Commands::Lint {
+            paths,
+            fix,
+            format,
+            rules,
+        } => {
+            if rules {
+                for rule in RULES {
+                    println!(
+                        "{:<24} {:<6} {}",
+                        rule.code,
+                        rule.default.as_str(),
+                        rule.summary
+                    );
+                }
+                return Ok(());
+            }
+            config.lint.check_rule_names()?;
+            let files = skill_files(&paths, &cli.skills_dir)?;
+
+            let mut diagnostics = Vec::new();
+            let mut skipped = 0;
+            for file in &files {
+                if fix {
+                    // A signature or lockfile pins the file's bytes; fixing would break it.
+                    match file.parent().and_then(pinned_by) {
+                        Some(pin) => warn!("Not fixing {}: it is pinned by {pin}", file.display()),
+                        None => match fix_skill_file(file, &config.lint) {
+                            Ok(0) => {}
+                            Ok(count) => {
+                                eprintln!("Fixed {count} problem(s) in {}", file.display())
+                            }
+                            Err(err) => warn!("Not fixing {}: {err:#}", file.display()),
+                        },
+                    }
+                }
+                match lint_skill_file(file, &config.lint) {
+                    Ok(lints) => diagnostics.extend(lints.into_iter().map(|lint| lint.diagnostic)),
+                    Err(err) => {
+                        warn!("Skipping {}: {err:#}", file.display());
+                        skipped += 1;
+                    }
+                }
+            }
+            match format {
+                OutputFormat::Table => println!("{}", render_diagnostics(&diagnostics)),
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&diagnostics)?),
+            }
+
+            let errors = diagnostics
+                .iter()
+                .filter(|d| d.severity == Severity::Error)
+                .count();
+            if errors > 0 || skipped > 0 {
+                return Err(anyhow::anyhow!(
+                    "{errors} lint error(s), {skipped} skill file(s) could not be linted"
+                ));
+            }
+        }
+        Commands::Convert { path, to, write } => {
+            let path = Path::new(&path);
+            let source = if path.is_dir() {
+                find_skill_file(path)
+                    .ok_or_else(|| anyhow::anyhow!("No skill file in {}", path.display()))?
+            } else {
+                path.to_path_buf()
+            };
+            let content = std::fs::read_to_string(&source)
+                .with_context(|| format!("Failed to read {}", source.display()))?;
+            let from = SkillFormat::detect(&source, &content);
+            let converted = convert(&content, from, to)
+                .with_context(|| format!("Cannot convert {}", source.display()))?;
+
+            if !write {
+                print!("{converted}");
+                return Ok(());
+            }
+            let dir = source.parent().unwrap_or(Path::new("."));
+            match pinned_by(dir) {
+                Some(SIGNATURE_FILE) => {
+                    return Err(anyhow::anyhow!(
+                        "{} is signed and converting would invalidate {SIGNATURE_FILE}; remove it, \
+convert, then run `genai sign` again",
+                        dir.display()
+                    ))
+                }
+                Some(_) => {
+                    return Err(anyhow::anyhow!(
+                        "{} is an installed package tracked by {LOCK_FILE}; convert its source and \
+reinstall instead",
+                        dir.display()
+                    ))
+                }
+                None => {}
+            }
+            let target = source.with_file_name(to.file_name());
+            if target != source && target.exists() {
+                return Err(anyhow::anyhow!("{} already exists", target.display()));
+            }
+            std::fs::write(&target, converted)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+            if target != source {
+                std::fs::remove_file(&source)?;
+            }
+            eprintln!(
+                "Converted {} ({}) to {} ({})",
+                source.display(),
+                from.as_str(),
+                target.display(),
+                to.as_str()
+            );
+        }
+        Commands::FakeServer { addr, rules } => {
+            let rules = match rules {
+                Some(path) => FakeRules::from_path(&path)?,
+                None => FakeRules::default(),
+            };
+            let server = FakeServer::bind(&addr, rules)?;
+            println!("Set GEMINI_BASE_URL=http://{}", server.local_addr()?);
+            server.serve()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn load_catalog(skills_dirs: &[String], trust: &TrustConfig) -> Result<SkillCatalog> {
+    let roots = resolve_skill_roots(skills_dirs)?;
+    if roots.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Skills directory not found. Use --skills-dir or set GENAI_SKILLS_DIR"
+        ));
     }
+    debug!(?roots, "Resolved skill roots");
 
+    let mut catalog = SkillCatalog::scan(&roots, trust)?;
+    if let Some((path, manifest)) = std::env::current_dir()
+        .ok()
+        .map(|cwd| ProjectManifest::discover(&cwd))
+        .transpose()?
+        .flatten()
+    {
+        debug!(path = %path.display(), pins = ?manifest.skills, "Applying version pins");
+        catalog.apply_pins(&manifest.skills)?;
+    }
+    catalog.drop_invalid();
+    for problem in &catalog.problems {
+        warn!("Skipping {}: {}", problem.path, problem.message);
+    }
+    for skill in &catalog.active() {
+        for warning in dataflow_warnings(skill) {
+            warn!("{warning}");
+        }
+    }
+    Ok(catalog)
+}
+
+/// Skill files under `paths`, or under every skill root when none is given.
+fn skill_files(paths: &[String], skills_dirs: &[String]) -> Result<Vec<PathBuf>> {
+    let files = if paths.is_empty() {
+        let roots = resolve_skill_roots(skills_dirs)?;
+        if roots.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Skills directory not found. Use --skills-dir or set GENAI_SKILLS_DIR"
+            ));
+        }
+        roots
+            .iter()
+            .flat_map(|root| find_skill_files(&root.path))
+            .collect::<Vec<_>>()
+    } else {
+        paths
+            .iter()
+            .flat_map(|path| find_skill_files(Path::new(path)))
+            .collect()
+    };
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("No skill files found"));
+    }
+    Ok(files)
+}
+
+/// `path` itself when it is a file, otherwise every skill file below it outside hidden
+/// directories.
+fn find_skill_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    walkdir::WalkDir::new(path)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && is_skill_file(entry.file_name()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Merges `--input-file` and `--set` values, prompting on a TTY for missing required inputs.
+fn resolve_skill_inputs(
+    skill: &Skill,
+    args: &InputArgs,
+    interactive: bool,
+) -> Result<BTreeMap<String, String>> {
+    let mut provided = match &args.input_file {
+        Some(path) => read_input_file(path)?,
+        None => BTreeMap::new(),
+    };
+    provided.extend(parse_assignments(&args.set)?);
+
+    let mut ask = |spec: &InputSpec| -> Result<String> {
+        let hint = spec
+            .description
+            .as_deref()
+            .map(|d| format!(" - {d}"))
+            .unwrap_or_default();
+        eprint!("{} ({}){hint}: ", spec.name, spec.input_type.as_str());
+        std::io::stderr().flush()?;
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+    let prompt: Option<InputPrompt<'_>> = if interactive { Some(&mut ask) } else { None };
+
+    resolve_inputs(&skill.metadata.inputs, &provided, prompt)
+}
+
+/// Asks a yes/no question on stderr; without a TTY the answer is no.
+fn confirm(question: &str, interactive: bool) -> Result<bool> {
+    if !interactive {
+        return Ok(false);
+    }
+    eprint!("{question} [y/N]: ");
+    std::io::stderr().flush()?;
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn load_skills(skills_dirs: &[String], trust: &TrustConfig) -> Result<Vec<Skill>> {
+    Ok(load_catalog(skills_dirs, trust)?.active())
+}
+
+/// Lets `type: skill` steps call any discovered skill.
+fn execution_options(skills: &[Skill]) -> ExecutionOptions {
+    ExecutionOptions {
+        skills: Arc::new(skills.to_vec()),
+        ..Default::default()
+    }
+}
+
+fn write_private_key(path: &Path, key: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    writeln!(file, "{key}")?;
     Ok(())
 }
+
+fn install_target(cwd: &Path, global: bool) -> Result<InstallTarget> {
+    if global {
+        InstallTarget::global()
+    } else {
+        InstallTarget::project(cwd)
+    }
+}
+
+fn format_limit(limit: Option<u64>) -> String {
+    limit.map_or_else(|| "-".to_string(), |limit| limit.to_string())
+}
@@ -0,0 +1,340 @@
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::skill::format::{read_document, SkillFormat, SKILL_FILE_NAMES};
+use crate::skill::model::Skill;
+use crate::skill::parser::{render_skill, uses_inheritance};
+
+/// Written at the root of every archive.
+pub const PACKAGE_FILE: &str = "package.yaml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub namespace: String,
+    pub version: String,
+    /// `sha256:<hex>` over the packaged files, see [`content_checksum`].
+    pub checksum: String,
+}
+
+impl PackageInfo {
+    pub fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}/{}", self.namespace, self.name)
+        }
+    }
+
+    pub fn archive_name(&self) -> String {
+        format!("{}-{}.tar.gz", self.name, self.version)
+    }
+
+    /// Directory the package is installed into, relative to a skills root.
+    pub fn install_dir(&self) -> Result<PathBuf> {
+        self.check_fields()?;
+        let mut dir = PathBuf::new();
+        for part in self.namespace.split('/').filter(|p| !p.is_empty()) {
+            dir.push(part);
+        }
+        Ok(dir.join(format!("{}@{}", self.name, self.version)))
+    }
+
+    /// `name`, `namespace` and `version` become path parts, so they must not be able
+    /// to leave the skills directory.
+    pub fn check_fields(&self) -> Result<()> {
+        path_part("name", &self.name)?;
+        if !self.namespace.is_empty() {
+            for part in self.namespace.split('/') {
+                path_part("namespace", part)?;
+            }
+        }
+        semver::Version::parse(&self.version)
+            .with_context(|| format!("Invalid package version '{}'", self.version))?;
+        Ok(())
+    }
+}
+
+fn path_part(field: &str, part: &str) -> Result<()> {
+    if part.is_empty() || part == "." || part == ".." || part.contains(['/', '\\']) {
+        return Err(anyhow!("Invalid package {field} '{part}'"));
+    }
+    Ok(())
+}
+
+/// A package's files as (relative path, contents), sorted by path.
+pub type PackageFiles = Vec<(String, Vec<u8>)>;
+
+/// Hash of every file's relative path and contents, independent of archive metadata.
+pub fn content_checksum(files: &PackageFiles) -> String {
+    let mut hasher = Sha256::new();
+    for (path, contents) in files {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(contents);
+    }
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+/// Collects the files of `skill`'s directory, skipping hidden entries. A skill file that
+/// uses `extends` or `include` is replaced by its flattened form, in the same format, so
+/// the package stands alone.
+pub fn collect_package(skill: &Skill) -> Result<(PackageInfo, PackageFiles)> {
+    let skill_file = Path::new(&skill.path);
+    let dir = skill_file
+        .parent()
+        .ok_or_else(|| anyhow!("Skill path has no directory: {}", skill.path))?;
+
+    let file_name = skill_file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut files = read_dir_files(dir)?;
+    if let Some((_, contents)) = files.iter_mut().find(|(path, _)| *path == file_name) {
+        let content = String::from_utf8_lossy(contents).to_string();
+        if uses_inheritance(skill_file, &content) {
+            let format = SkillFormat::detect(skill_file, &content);
+            *contents = render_skill(skill, format)?.into_bytes();
+        }
+    }
+
+    let info = PackageInfo {
+        name: skill.metadata.name.clone(),
+        namespace: skill.namespace.clone(),
+        version: skill.metadata.version.clone(),
+        checksum: content_checksum(&files),
+    };
+    Ok((info, files))
+}
+
+/// Every non-hidden file below `dir`. Symlinks are refused rather than skipped or followed.
+pub fn read_dir_files(dir: &Path) -> Result<PackageFiles> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !entry.file_name().to_string_lossy().starts_with('.'));
+    for entry in walker {
+        let entry = entry?;
+        // Scanning follows links, so a linked file could change without changing the bundle.
+        if entry.path_is_symlink() {
+            return Err(anyhow!(
+                "{} is a symlink; copy the file into the skill directory instead",
+                entry.path().display()
+            ));
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry
+            .path()
+            .strip_prefix(dir)?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if rel == PACKAGE_FILE {
+            continue;
+        }
+        files.push((rel, std::fs::read(entry.path())?));
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// Writes `<out_dir>/<name>-<version>.tar.gz` with fixed timestamps, so packing the
+/// same content twice produces the same bytes.
+pub fn write_archive(info: &PackageInfo, files: &PackageFiles, out_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(out_dir)?;
+    let path = out_dir.join(info.archive_name());
+    let file = std::fs::File::create(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let manifest = serde_yaml::to_string(info)?;
+    append(&mut builder, PACKAGE_FILE, manifest.as_bytes())?;
+    for (rel, contents) in files {
+        append(&mut builder, rel, contents)?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(path)
+}
+
+fn append<W: std::io::Write>(builder: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Reads an archive and checks its files against the checksum in `package.yaml`.
+pub fn read_archive(path: &Path) -> Result<(PackageInfo, PackageFiles)> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+    let mut info = None;
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let rel = entry.path()?.into_owned();
+        if rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(anyhow!(
+                "Archive entry escapes the package: {}",
+                rel.display()
+            ));
+        }
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        if rel == PACKAGE_FILE {
+            info = Some(serde_yaml::from_slice::<PackageInfo>(&contents)?);
+        } else {
+            files.push((rel, contents));
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let info = info.ok_or_else(|| anyhow!("{} has no {PACKAGE_FILE}", path.display()))?;
+    info.check_fields()
+        .with_context(|| format!("Bad {PACKAGE_FILE} in {}", path.display()))?;
+    let actual = content_checksum(&files);
+    if actual != info.checksum {
+        return Err(anyhow!(
+            "Checksum mismatch for {}: package.yaml says {}, contents hash to {actual}",
+            path.display(),
+            info.checksum
+        ));
+    }
+    check_skill_identity(&info, &files)
+        .with_context(|| format!("{} does not match its {PACKAGE_FILE}", path.display()))?;
+    Ok((info, files))
+}
+
+/// The packaged skill file must declare the name and version the package claims.
+fn check_skill_identity(info: &PackageInfo, files: &PackageFiles) -> Result<()> {
+    let (name, contents) = SKILL_FILE_NAMES
+        .iter()
+        .find_map(|name| files.iter().find(|(path, _)| path == name))
+        .ok_or_else(|| anyhow!("Package has no skill file"))?;
+    let content = String::from_utf8_lossy(contents);
+    let format = SkillFormat::detect(Path::new(name), &content);
+    let frontmatter = read_document(&content, format)?.frontmatter;
+    for (key, expected) in [("name", &info.name), ("version", &info.version)] {
+        let actual = frontmatter.get(key).and_then(|value| value.as_str());
+        if actual != Some(expected.as_str()) {
+            return Err(anyhow!(
+                "{name} has {key} {}, package.yaml has '{expected}'",
+                actual.map_or("missing".to_string(), |actual| format!("'{actual}'"))
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_package, content_checksum, read_archive, write_archive, PackageInfo};
+    use crate::skill::model::Skill;
+    use crate::skill::parser::parse_skill_file;
+    use crate::util::testing::{write_skill, TempRoot};
+
+    #[test]
+    fn archives_round_trip_and_detect_tampering() {
+        let dir = TempRoot::new("archive");
+
+        let files = vec![
+            (
+                "SKILL.md".to_string(),
+                b"---\nname: x\nversion: 1.2.0\n---\n".to_vec(),
+            ),
+            ("scripts/run.sh".to_string(), b"echo hi\n".to_vec()),
+        ];
+        let info = PackageInfo {
+            name: "x".to_string(),
+            namespace: "git".to_string(),
+            version: "1.2.0".to_string(),
+            checksum: content_checksum(&files),
+        };
+
+        let path = write_archive(&info, &files, &dir).expect("pack should succeed");
+        assert!(path.ends_with("x-1.2.0.tar.gz"));
+        let (read_info, read_files) = read_archive(&path).expect("archive should verify");
+        assert_eq!(read_info, info);
+        assert_eq!(read_files, files);
+
+        let tampered = PackageInfo {
+            checksum: "sha256:00".to_string(),
+            ..info.clone()
+        };
+        let path = write_archive(&tampered, &files, &dir).unwrap();
+        assert!(read_archive(&path).is_err());
+
+        for (namespace, name, version) in [
+            ("../..", "x", "1.2.0"),
+            ("", "a\\b", "1.2.0"),
+            ("", "x", "../1"),
+        ] {
+            let escaping = PackageInfo {
+                name: name.to_string(),
+                namespace: namespace.to_string(),
+                version: version.to_string(),
+                checksum: content_checksum(&files),
+            };
+            assert!(escaping.install_dir().is_err(), "{escaping:?}");
+        }
+        let renamed = PackageInfo {
+            name: "y".to_string(),
+            ..info
+        };
+        let path = write_archive(&renamed, &files, &dir).unwrap();
+        assert!(read_archive(&path).is_err());
+    }
+
+    #[test]
+    fn flattened_skills_keep_their_documentation() {
+        let root = TempRoot::new("archive-flat");
+        write_skill(&root.join("base"), "base", "d", "1.0.0");
+        std::fs::create_dir_all(root.join("child")).unwrap();
+        let path = root.join("child/SKILL.md");
+        std::fs::write(
+            &path,
+            "---\nname: child\nextends: ../base\n---\n# Overview\n\nWrites notes.\n\n## Rules\n\n- be brief\n\n\
+### Step: respond\n\nSays hi.\n\n```genai-step\nid: respond\ntype: output\ntemplate: hi\n```\n",
+        )
+        .unwrap();
+
+        let (metadata, markdown_body, steps) = parse_skill_file(&path).unwrap();
+        let skill = Skill {
+            metadata,
+            markdown_body,
+            steps,
+            path: path.to_string_lossy().to_string(),
+            namespace: String::new(),
+        };
+        let (_, files) = collect_package(&skill).unwrap();
+        let flattened = String::from_utf8(files[0].1.clone()).unwrap();
+
+        assert!(!flattened.contains("extends"));
+        assert!(flattened.contains("# Overview\n\nWrites notes.\n\n## Rules\n\n- be brief\n"));
+        assert_eq!(flattened.matches("### Step: respond").count(), 1);
+        assert!(flattened.contains("Says hi."));
+    }
+}
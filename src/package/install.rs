@@ -0,0 +1,414 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::package::archive::{collect_package, read_archive, PackageFiles, PackageInfo};
+use crate::package::lock::{LockedSkill, Lockfile, LOCK_FILE};
+use crate::package::registry::{archive_path, RegistryIndex, INDEX_FILE};
+use crate::package::signing::SIGNATURE_FILE;
+use crate::skill::catalog::parse_reference;
+use crate::skill::format::find_skill_file;
+use crate::skill::manifest::ProjectManifest;
+use crate::skill::model::Skill;
+use crate::skill::parser::parse_skill_file;
+
+/// Managed skills directory and the lockfile that describes it.
+#[derive(Debug, Clone)]
+pub struct InstallTarget {
+    pub skills_dir: PathBuf,
+    pub lock_path: PathBuf,
+}
+
+impl InstallTarget {
+    /// `.genai/skills` and `genai.lock` next to the nearest `genai.yaml`, or in `cwd`.
+    pub fn project(cwd: &Path) -> Result<Self> {
+        let root = ProjectManifest::discover(cwd)?
+            .and_then(|(path, _)| path.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| cwd.to_path_buf());
+        Ok(Self::at(&root, &root.join(".genai").join("skills")))
+    }
+
+    /// `~/GenAI/skills` with `~/GenAI/genai.lock`.
+    pub fn global() -> Result<Self> {
+        let home = std::env::var("HOME").context("HOME is not set")?;
+        let root = PathBuf::from(home).join("GenAI");
+        Ok(Self::at(&root, &root.join("skills")))
+    }
+
+    fn at(root: &Path, skills_dir: &Path) -> Self {
+        Self {
+            skills_dir: skills_dir.to_path_buf(),
+            lock_path: root.join(LOCK_FILE),
+        }
+    }
+
+    fn base_dir(&self) -> &Path {
+        self.lock_path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PackageSource {
+    Archive(PathBuf),
+    Directory(PathBuf),
+    Registry { dir: PathBuf, reference: String },
+}
+
+impl PackageSource {
+    /// An archive file, a skill directory, a registry directory plus a reference, or a
+    /// bare `name[@requirement]` looked up in `registry`.
+    pub fn from_arg(arg: &str, reference: Option<&str>, registry: Option<&Path>) -> Result<Self> {
+        let path = PathBuf::from(arg);
+        if path.is_file() {
+            return Ok(Self::Archive(path));
+        }
+        if path.join(INDEX_FILE).is_file() {
+            let reference = reference
+                .ok_or_else(|| anyhow!("Name the skill to install from registry {arg}"))?;
+            return Ok(Self::Registry {
+                dir: path,
+                reference: reference.to_string(),
+            });
+        }
+        if find_skill_file(&path).is_some() {
+            return Ok(Self::Directory(path));
+        }
+        match registry {
+            Some(dir) => Ok(Self::Registry {
+                dir: dir.to_path_buf(),
+                reference: arg.to_string(),
+            }),
+            None => Err(anyhow!(
+                "{arg} is not an archive, skill directory or registry, and no registry is configured"
+            )),
+        }
+    }
+
+    /// Rebuilds the source recorded in a lockfile entry.
+    fn from_locked(locked: &LockedSkill, base: &Path) -> Result<Self> {
+        let (kind, path) = locked
+            .source
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid lock source '{}'", locked.source))?;
+        let path = base.join(path);
+        match kind {
+            "archive" => Ok(Self::Archive(path)),
+            "dir" => Ok(Self::Directory(path)),
+            "registry" => Ok(Self::Registry {
+                dir: path,
+                reference: format!("{}@={}", locked.name, locked.version),
+            }),
+            other => Err(anyhow!("Unknown lock source kind '{other}'")),
+        }
+    }
+
+    fn describe(&self, base: &Path) -> String {
+        let (kind, path) = match self {
+            Self::Archive(path) => ("archive", path),
+            Self::Directory(path) => ("dir", path),
+            Self::Registry { dir, .. } => ("registry", dir),
+        };
+        format!("{kind}:{}", relative_to(base, path).display())
+    }
+
+    pub fn load(&self) -> Result<(PackageInfo, PackageFiles)> {
+        match self {
+            Self::Archive(path) => read_archive(path),
+            Self::Directory(dir) => {
+                let path = find_skill_file(dir)
+                    .ok_or_else(|| anyhow!("No skill file in {}", dir.display()))?;
+                let (metadata, markdown_body, steps) = parse_skill_file(&path)?;
+                collect_package(&Skill {
+                    metadata,
+                    markdown_body,
+                    steps,
+                    path: path.to_string_lossy().to_string(),
+                    namespace: String::new(),
+                })
+            }
+            Self::Registry { dir, reference } => {
+                let index = RegistryIndex::load(dir)?;
+                let entry = index.resolve(reference)?;
+                let (info, files) = read_archive(&archive_path(dir, entry))?;
+                if info != entry.info {
+                    return Err(anyhow!(
+                        "Registry index entry for {} does not match its archive",
+                        entry.info.qualified_name()
+                    ));
+                }
+                Ok((info, files))
+            }
+        }
+    }
+}
+
+/// Installs one package and records it in the lockfile. A package already locked at the
+/// same version must still have the locked checksum.
+pub fn install(target: &InstallTarget, source: &PackageSource) -> Result<LockedSkill> {
+    let (info, files) = source.load()?;
+    let mut lock = Lockfile::load(&target.lock_path)?;
+    if let Some(locked) = lock.find(&info.qualified_name(), &info.version) {
+        check_checksum(locked, &info)?;
+    }
+
+    write_package(target, &info, &files)?;
+    let locked = LockedSkill {
+        name: info.qualified_name(),
+        version: info.version.clone(),
+        checksum: info.checksum.clone(),
+        source: source.describe(target.base_dir()),
+    };
+    lock.upsert(locked.clone());
+    lock.save(&target.lock_path)?;
+    Ok(locked)
+}
+
+/// Reinstalls exactly what the lockfile records, failing on any checksum drift.
+pub fn install_locked(target: &InstallTarget) -> Result<Vec<LockedSkill>> {
+    let lock = Lockfile::load(&target.lock_path)?;
+    for locked in &lock.skills {
+        let source = PackageSource::from_locked(locked, target.base_dir())?;
+        let (info, files) = source
+            .load()
+            .with_context(|| format!("Failed to fetch {}@{}", locked.name, locked.version))?;
+        if info.version != locked.version {
+            return Err(anyhow!(
+                "{} resolved to {} but the lockfile has {}",
+                locked.name,
+                info.version,
+                locked.version
+            ));
+        }
+        check_checksum(locked, &info)?;
+        write_package(target, &info, &files)?;
+    }
+    Ok(lock.skills)
+}
+
+/// Removes every installed version matching `name[@requirement]`.
+pub fn uninstall(target: &InstallTarget, reference: &str) -> Result<Vec<LockedSkill>> {
+    let (name, requirement) = parse_reference(reference)?;
+    let mut lock = Lockfile::load(&target.lock_path)?;
+
+    let (removed, kept): (Vec<_>, Vec<_>) = lock.skills.into_iter().partition(|locked| {
+        let name_matches = locked.name == name || locked.name.rsplit('/').next() == Some(name);
+        let version_matches = requirement.as_ref().is_none_or(|req| {
+            semver::Version::parse(&locked.version).is_ok_and(|v| req.matches(&v))
+        });
+        name_matches && version_matches
+    });
+    if removed.is_empty() {
+        return Err(anyhow!("No installed skill matches '{reference}'"));
+    }
+    let mut names = removed
+        .iter()
+        .map(|locked| locked.name.as_str())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    if names.len() > 1 {
+        return Err(anyhow!(
+            "Skill name '{name}' is ambiguous, use one of: {}",
+            names.join(", ")
+        ));
+    }
+
+    for locked in &removed {
+        let (namespace, skill_name) = match locked.name.rsplit_once('/') {
+            Some((namespace, skill_name)) => (namespace.to_string(), skill_name.to_string()),
+            None => (String::new(), locked.name.clone()),
+        };
+        let info = PackageInfo {
+            name: skill_name,
+            namespace,
+            version: locked.version.clone(),
+            checksum: locked.checksum.clone(),
+        };
+        let dir = install_path(target, &info)?;
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+    }
+
+    lock.skills = kept;
+    lock.save(&target.lock_path)?;
+    Ok(removed)
+}
+
+/// The file that pins the exact bytes of the skill in `dir`, which rewriting it in
+/// place would break: its signature, or the lockfile of an installed package.
+pub fn pinned_by(dir: &Path) -> Option<&'static str> {
+    if dir.join(SIGNATURE_FILE).exists() {
+        return Some(SIGNATURE_FILE);
+    }
+    // Installed packages live in `<name>@<version>` directories.
+    let installed = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.rsplit_once('@'))
+        .is_some_and(|(_, version)| semver::Version::parse(version).is_ok());
+    installed.then_some(LOCK_FILE)
+}
+
+fn check_checksum(locked: &LockedSkill, info: &PackageInfo) -> Result<()> {
+    if locked.checksum != info.checksum {
+        return Err(anyhow!(
+            "Checksum mismatch for {}@{}: locked {}, got {}",
+            locked.name,
+            locked.version,
+            locked.checksum,
+            info.checksum
+        ));
+    }
+    Ok(())
+}
+
+/// Where `info` is installed; refuses anything that would land outside `skills_dir`.
+fn install_path(target: &InstallTarget, info: &PackageInfo) -> Result<PathBuf> {
+    let rel = info.install_dir()?;
+    let dir = target.skills_dir.join(&rel);
+    if !rel.components().all(|c| matches!(c, Component::Normal(_)))
+        || !dir.starts_with(&target.skills_dir)
+    {
+        return Err(anyhow!(
+            "{} would be installed outside {}",
+            info.qualified_name(),
+            target.skills_dir.display()
+        ));
+    }
+    Ok(dir)
+}
+
+fn write_package(target: &InstallTarget, info: &PackageInfo, files: &PackageFiles) -> Result<()> {
+    let dir = install_path(target, info)?;
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    for (rel, contents) in files {
+        let path = dir.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// `path` relative to `base` (using `..` where needed), so lockfiles stay portable.
+fn relative_to(base: &Path, path: &Path) -> PathBuf {
+    let (Ok(base), Ok(path)) = (base.canonicalize(), path.canonicalize()) else {
+        return path.to_path_buf();
+    };
+    let base_parts = base.components().collect::<Vec<_>>();
+    let path_parts = path.components().collect::<Vec<_>>();
+    let common = base_parts
+        .iter()
+        .zip(&path_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base_parts.len() {
+        relative.push("..");
+    }
+    for part in &path_parts[common..] {
+        relative.push(part);
+    }
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{install, install_locked, pinned_by, uninstall, InstallTarget, PackageSource};
+    use crate::package::archive::{collect_package, write_archive};
+    use crate::package::lock::{LockedSkill, Lockfile, LOCK_FILE};
+    use crate::package::registry::RegistryIndex;
+    use crate::package::signing::{TrustConfig, SIGNATURE_FILE};
+    use crate::skill::scanner::scan_skills;
+    use crate::util::testing::{write_skill, TempRoot};
+
+    #[test]
+    fn installs_from_a_registry_locks_and_reinstalls() {
+        let root = TempRoot::new("install");
+        let src = root.join("src");
+        let registry = root.join("registry");
+        let project = root.join("project");
+        std::fs::create_dir_all(&project).unwrap();
+
+        let mut index = RegistryIndex::default();
+        for version in ["1.0.0", "1.3.0", "2.0.0"] {
+            write_skill(&src.join(format!("greet-{version}")), "greet", "d", version);
+        }
+        for skill in scan_skills(&src.to_string_lossy(), &TrustConfig::default())
+            .unwrap()
+            .skills
+        {
+            let (info, files) = collect_package(&skill).unwrap();
+            let archive = write_archive(&info, &files, &registry).unwrap();
+            index.publish(
+                info,
+                archive.file_name().unwrap().to_string_lossy().to_string(),
+            );
+        }
+        index.save(&registry).unwrap();
+
+        let target = InstallTarget::project(&project).unwrap();
+        let source =
+            PackageSource::from_arg(&registry.to_string_lossy(), Some("greet@^1"), None).unwrap();
+        let locked = install(&target, &source).expect("install should succeed");
+        assert_eq!(locked.version, "1.3.0");
+        assert_eq!(locked.source, "registry:../registry");
+
+        let installed = project.join(".genai/skills/greet@1.3.0/SKILL.md");
+        std::fs::remove_dir_all(installed.parent().unwrap()).unwrap();
+        install_locked(&target).expect("lockfile should reinstall");
+        assert!(installed.is_file());
+
+        uninstall(&target, "greet").expect("uninstall should succeed");
+        assert!(!installed.exists());
+        assert!(Lockfile::load(&target.lock_path).unwrap().skills.is_empty());
+    }
+
+    #[test]
+    fn ambiguous_bare_names_are_not_uninstalled() {
+        let root = TempRoot::new("uninstall");
+        let target = InstallTarget::project(&root).unwrap();
+        let locked = |name: &str| LockedSkill {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            checksum: "sha256:00".to_string(),
+            source: "dir:.".to_string(),
+        };
+        let lock = Lockfile {
+            skills: vec![locked("git/greet"), locked("docs/greet")],
+        };
+        lock.save(&target.lock_path).unwrap();
+
+        let err = uninstall(&target, "greet").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Skill name 'greet' is ambiguous, use one of: docs/greet, git/greet"
+        );
+        assert_eq!(Lockfile::load(&target.lock_path).unwrap().skills.len(), 2);
+
+        uninstall(&target, "git/greet").expect("qualified name should uninstall");
+        let kept = Lockfile::load(&target.lock_path).unwrap().skills;
+        assert_eq!(kept, vec![locked("docs/greet")]);
+    }
+
+    #[test]
+    fn signed_and_installed_skills_are_pinned() {
+        let root = TempRoot::new("pinned");
+        for dir in ["plain", "signed", "greet@1.0.0", "notes@latest"] {
+            write_skill(&root.join(dir), "greet", "d", "1.0.0");
+        }
+        std::fs::write(root.join("signed").join(SIGNATURE_FILE), "").unwrap();
+
+        assert_eq!(pinned_by(&root.join("plain")), None);
+        assert_eq!(pinned_by(&root.join("signed")), Some(SIGNATURE_FILE));
+        assert_eq!(pinned_by(&root.join("greet@1.0.0")), Some(LOCK_FILE));
+        assert_eq!(pinned_by(&root.join("notes@latest")), None);
+    }
+}
@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const LOCK_FILE: &str = "genai.lock";
+
+/// Exact versions and checksums of installed skills, meant to be committed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub skills: Vec<LockedSkill>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedSkill {
+    /// Qualified name.
+    pub name: String,
+    pub version: String,
+    pub checksum: String,
+    /// Where it was installed from, e.g. `registry:../skills-registry`.
+    pub source: String,
+}
+
+impl Lockfile {
+    /// Loads `path`; a missing lockfile is empty.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_yaml::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn find(&self, name: &str, version: &str) -> Option<&LockedSkill> {
+        self.skills
+            .iter()
+            .find(|s| s.name == name && s.version == version)
+    }
+
+    pub fn upsert(&mut self, skill: LockedSkill) {
+        self.skills
+            .retain(|s| s.name != skill.name || s.version != skill.version);
+        self.skills.push(skill);
+        self.skills
+            .sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    }
+}
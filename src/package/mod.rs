@@ -0,0 +1,5 @@
+pub mod archive;
+pub mod install;
+pub mod lock;
+pub mod registry;
+pub mod signing;
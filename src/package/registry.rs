@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::package::archive::PackageInfo;
+use crate::skill::catalog::parse_reference;
+
+/// Index file at the root of a file-based registry directory.
+pub const INDEX_FILE: &str = "index.yaml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistryIndex {
+    #[serde(default)]
+    pub packages: Vec<RegistryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    #[serde(flatten)]
+    pub info: PackageInfo,
+    /// Archive path relative to the registry directory.
+    pub archive: String,
+}
+
+impl RegistryIndex {
+    /// Loads `dir/index.yaml`; a directory without one is an empty registry.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::write(dir.join(INDEX_FILE), serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Adds or replaces the entry for the same name and version.
+    pub fn publish(&mut self, info: PackageInfo, archive: String) {
+        self.packages.retain(|entry| {
+            entry.info.qualified_name() != info.qualified_name()
+                || entry.info.version != info.version
+        });
+        self.packages.push(RegistryEntry { info, archive });
+        self.packages.sort_by(|a, b| {
+            (a.info.qualified_name(), &a.info.version)
+                .cmp(&(b.info.qualified_name(), &b.info.version))
+        });
+    }
+
+    /// The highest version matching `name[@requirement]`, by qualified or bare name.
+    pub fn resolve(&self, reference: &str) -> Result<&RegistryEntry> {
+        let (name, requirement) = parse_reference(reference)?;
+        self.packages
+            .iter()
+            .filter(|entry| entry.info.qualified_name() == name || entry.info.name == name)
+            .filter_map(|entry| {
+                let version = semver::Version::parse(&entry.info.version).ok()?;
+                requirement
+                    .as_ref()
+                    .is_none_or(|req| req.matches(&version))
+                    .then_some((version, entry))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0))
+            .map(|(_, entry)| entry)
+            .ok_or_else(|| anyhow!("No package in the registry matches '{reference}'"))
+    }
+}
+
+pub fn archive_path(registry: &Path, entry: &RegistryEntry) -> PathBuf {
+    registry.join(&entry.archive)
+}
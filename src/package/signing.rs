@@ -0,0 +1,222 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::package::archive::{collect_package, content_checksum};
+use crate::skill::model::Skill;
+
+/// Detached signature stored next to SKILL.md; packaged and installed with the skill.
+pub const SIGNATURE_FILE: &str = "SKILL.sig";
+
+/// Prefix of every signed message, so a skill signature cannot be replayed elsewhere.
+const SIGNATURE_CONTEXT: &str = "genai-skill-signature-v1\n";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustPolicy {
+    /// Signatures are not checked.
+    #[default]
+    Off,
+    /// Unsigned or tampered skills load with a warning.
+    Warn,
+    /// Unsigned or tampered skills are refused at scan time.
+    Enforce,
+}
+
+/// `trust:` section of the config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TrustConfig {
+    pub policy: TrustPolicy,
+    pub keys: Vec<TrustedKey>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrustedKey {
+    pub name: String,
+    /// Base64 ed25519 public key, as written by `genai keygen`.
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillSignature {
+    pub public_key: String,
+    /// Checksum of the canonical bundle at signing time.
+    pub checksum: String,
+    pub signature: String,
+}
+
+/// A new key pair as base64 (private seed, public key).
+pub fn generate_keypair() -> (String, String) {
+    let key = SigningKey::generate(&mut rand_core::OsRng);
+    (
+        BASE64.encode(key.to_bytes()),
+        BASE64.encode(key.verifying_key().to_bytes()),
+    )
+}
+
+/// Checksum of the skill as it would be packaged, without its signature file.
+pub fn bundle_checksum(skill: &Skill) -> Result<String> {
+    let (_, mut files) = collect_package(skill)?;
+    files.retain(|(path, _)| path != SIGNATURE_FILE);
+    Ok(content_checksum(&files))
+}
+
+/// Signs `skill` with a base64 private key and writes `SKILL.sig` beside it.
+pub fn sign_skill(skill: &Skill, private_key: &str) -> Result<SkillSignature> {
+    let seed: [u8; 32] = BASE64
+        .decode(private_key.trim())
+        .context("Private key is not base64")?
+        .try_into()
+        .map_err(|_| anyhow!("Private key must be 32 bytes"))?;
+    let key = SigningKey::from_bytes(&seed);
+
+    let checksum = bundle_checksum(skill)?;
+    let signature = key.sign(format!("{SIGNATURE_CONTEXT}{checksum}").as_bytes());
+    let signed = SkillSignature {
+        public_key: BASE64.encode(key.verifying_key().to_bytes()),
+        checksum,
+        signature: BASE64.encode(signature.to_bytes()),
+    };
+
+    std::fs::write(signature_path(skill)?, serde_yaml::to_string(&signed)?)?;
+    Ok(signed)
+}
+
+/// Checks that `skill` is signed by a trusted key and unchanged since; returns the key name.
+pub fn verify_skill(skill: &Skill, trust: &TrustConfig) -> Result<String> {
+    let path = signature_path(skill)?;
+    if !path.exists() {
+        return Err(anyhow!("unsigned (no {SIGNATURE_FILE})"));
+    }
+    let signed: SkillSignature = serde_yaml::from_str(&std::fs::read_to_string(&path)?)
+        .with_context(|| format!("Invalid {SIGNATURE_FILE}"))?;
+
+    let trusted = trust
+        .keys
+        .iter()
+        .find(|key| key.public_key.trim() == signed.public_key.trim())
+        .ok_or_else(|| anyhow!("signed by an untrusted key"))?;
+
+    let checksum = bundle_checksum(skill)?;
+    if checksum != signed.checksum {
+        return Err(anyhow!(
+            "tampered: contents hash to {checksum}, signature covers {}",
+            signed.checksum
+        ));
+    }
+
+    let key_bytes: [u8; 32] = BASE64
+        .decode(signed.public_key.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("public key must be 32 bytes"))?;
+    let signature_bytes: [u8; 64] = BASE64
+        .decode(signed.signature.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("signature must be 64 bytes"))?;
+    VerifyingKey::from_bytes(&key_bytes)?
+        .verify(
+            format!("{SIGNATURE_CONTEXT}{checksum}").as_bytes(),
+            &Signature::from_bytes(&signature_bytes),
+        )
+        .map_err(|_| anyhow!("signature does not match"))?;
+
+    Ok(trusted.name.clone())
+}
+
+/// Applies the trust policy to a freshly loaded skill.
+pub fn enforce_trust(skill: &Skill, trust: &TrustConfig) -> Result<()> {
+    match trust.policy {
+        TrustPolicy::Off => Ok(()),
+        TrustPolicy::Warn => {
+            if let Err(err) = verify_skill(skill, trust) {
+                warn!(skill = %skill.qualified_name(), "Untrusted skill: {err}");
+            }
+            Ok(())
+        }
+        TrustPolicy::Enforce => verify_skill(skill, trust)
+            .map(|_| ())
+            .map_err(|err| anyhow!("Refusing untrusted skill: {err}")),
+    }
+}
+
+fn signature_path(skill: &Skill) -> Result<std::path::PathBuf> {
+    Path::new(&skill.path)
+        .parent()
+        .map(|dir| dir.join(SIGNATURE_FILE))
+        .ok_or_else(|| anyhow!("Skill path has no directory: {}", skill.path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        enforce_trust, generate_keypair, sign_skill, verify_skill, TrustConfig, TrustPolicy,
+        TrustedKey,
+    };
+    use crate::skill::scanner::scan_skills;
+    use crate::util::testing::{skill_md, write_skill, TempRoot};
+
+    #[test]
+    fn verifies_signed_skills_and_rejects_tampering_and_unknown_keys() {
+        let root = TempRoot::new("signing");
+        let dir = root.join("greet");
+        let skill_md = skill_md("greet", "d", "1.0.0");
+        write_skill(&dir, "greet", "d", "1.0.0");
+
+        let (private_key, public_key) = generate_keypair();
+        let trust = TrustConfig {
+            policy: TrustPolicy::Enforce,
+            keys: vec![TrustedKey {
+                name: "platform".to_string(),
+                public_key,
+            }],
+        };
+        let load = || {
+            scan_skills(&root.to_string_lossy(), &TrustConfig::default())
+                .unwrap()
+                .skills
+                .remove(0)
+        };
+
+        assert!(enforce_trust(&load(), &trust).is_err());
+        sign_skill(&load(), &private_key).expect("signing should succeed");
+        assert_eq!(verify_skill(&load(), &trust).unwrap(), "platform");
+        assert_eq!(
+            scan_skills(&root.to_string_lossy(), &trust)
+                .unwrap()
+                .skills
+                .len(),
+            1
+        );
+
+        std::fs::write(dir.join("SKILL.md"), skill_md.replace("# greet", "# evil")).unwrap();
+        let report = scan_skills(&root.to_string_lossy(), &trust).unwrap();
+        assert!(report.skills.is_empty());
+        assert!(report.problems[0].message.contains("tampered"));
+
+        let (_, other_public) = generate_keypair();
+        let stranger = TrustConfig {
+            keys: vec![TrustedKey {
+                name: "other".to_string(),
+                public_key: other_public,
+            }],
+            ..trust
+        };
+        assert!(verify_skill(&load(), &stranger).is_err());
+
+        #[cfg(unix)]
+        {
+            std::fs::write(dir.join("SKILL.md"), &skill_md).unwrap();
+            std::fs::write(root.join("outside.sh"), "echo swapped\n").unwrap();
+            std::os::unix::fs::symlink(root.join("outside.sh"), dir.join("run.sh")).unwrap();
+            let err = verify_skill(&load(), &trust).unwrap_err();
+            assert!(err.to_string().contains("symlink"), "{err:#}");
+            assert!(sign_skill(&load(), &private_key).is_err());
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Context, Result};
+use semver::{Version, VersionReq};
+
+use crate::package::signing::TrustConfig;
+use crate::skill::model::Skill;
+use crate::skill::roots::SkillRoot;
+use crate::skill::scanner::{scan_skills, ScanProblem};
+use crate::skill::validator::{validate_calls, validate_skill};
+
+/// A skill together with the root it was discovered in.
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    pub skill: Skill,
+    pub root: SkillRoot,
+    /// Path of the higher-precedence skill hiding this one.
+    pub shadowed_by: Option<String>,
+    /// Project pin this version does not satisfy.
+    pub excluded_by_pin: Option<String>,
+}
+
+/// Every skill found across all roots, in precedence order.
+#[derive(Debug, Clone, Default)]
+pub struct SkillCatalog {
+    pub entries: Vec<CatalogEntry>,
+    /// SKILL.md files that were skipped because they failed to load.
+    pub problems: Vec<ScanProblem>,
+}
+
+impl SkillCatalog {
+    /// Scans `roots` in order. The first root holding a qualified name owns it: the same
+    /// name in any later root is shadowed, whatever its version. Several versions of a
+    /// skill (such as installed `name@version` directories) stay side by side only within
+    /// that root.
+    pub fn scan(roots: &[SkillRoot], trust: &TrustConfig) -> Result<Self> {
+        let mut entries: Vec<CatalogEntry> = Vec::new();
+        let mut problems = Vec::new();
+        // Qualified name -> (index of the owning root, path of its first skill).
+        let mut owners: HashMap<String, (usize, String)> = HashMap::new();
+        let mut versions: HashMap<String, String> = HashMap::new();
+
+        for (index, root) in roots.iter().enumerate() {
+            let report = scan_skills(&root.path.to_string_lossy(), trust)?;
+            problems.extend(report.problems);
+
+            for skill in report.skills {
+                let name = skill.qualified_name();
+                let version = format!("{name}@{}", skill.metadata.version);
+                let (owner, first) = owners
+                    .entry(name)
+                    .or_insert_with(|| (index, skill.path.clone()))
+                    .clone();
+                let shadowed_by = if owner != index {
+                    Some(first)
+                } else {
+                    versions.get(&version).cloned()
+                };
+                if shadowed_by.is_none() {
+                    versions.insert(version, skill.path.clone());
+                }
+                entries.push(CatalogEntry {
+                    skill,
+                    root: root.clone(),
+                    shadowed_by,
+                    excluded_by_pin: None,
+                });
+            }
+        }
+
+        Ok(Self { entries, problems })
+    }
+
+    /// Hides versions that do not satisfy the project's pins, keyed by qualified or bare
+    /// name, e.g. `auto-commit-msg: ^1.2`.
+    pub fn apply_pins(&mut self, pins: &BTreeMap<String, String>) -> Result<()> {
+        for (name, requirement) in pins {
+            let req = VersionReq::parse(requirement)
+                .with_context(|| format!("Invalid version pin for {name}: {requirement}"))?;
+            for entry in self
+                .entries
+                .iter_mut()
+                .filter(|entry| entry.skill.matches_name(name))
+            {
+                if !entry.skill.metadata.semver().is_ok_and(|v| req.matches(&v)) {
+                    entry.excluded_by_pin = Some(requirement.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops active skills that fail validation, or call a skill that is missing, too
+    /// privileged or invalid, and reports them as problems so the rest stay usable.
+    pub fn drop_invalid(&mut self) {
+        loop {
+            let active = self.active();
+            let failures = active
+                .iter()
+                .filter_map(|skill| {
+                    validate_skill(skill)
+                        .and_then(|_| validate_calls(skill, &active))
+                        .err()
+                        .map(|err| (skill.path.clone(), format!("{err:#}")))
+                })
+                .collect::<Vec<_>>();
+            if failures.is_empty() {
+                return;
+            }
+            self.entries
+                .retain(|entry| !failures.iter().any(|(path, _)| *path == entry.skill.path));
+            self.problems.extend(
+                failures
+                    .into_iter()
+                    .map(|(path, message)| ScanProblem { path, message }),
+            );
+        }
+    }
+
+    /// The skills that are visible after shadowing and pins, every version included.
+    pub fn active(&self) -> Vec<Skill> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.shadowed_by.is_none() && entry.excluded_by_pin.is_none())
+            .map(|entry| entry.skill.clone())
+            .collect()
+    }
+}
+
+/// Only the highest version of each skill, for prompts that should see one of each.
+pub fn latest_versions(skills: &[Skill]) -> Vec<Skill> {
+    let mut latest: Vec<Skill> = Vec::new();
+    for skill in skills {
+        match latest
+            .iter_mut()
+            .find(|s| s.qualified_name() == skill.qualified_name())
+        {
+            Some(existing) if version_of(skill) > version_of(existing) => *existing = skill.clone(),
+            Some(_) => {}
+            None => latest.push(skill.clone()),
+        }
+    }
+    latest
+}
+
+/// Splits `name@^1.2` into the name and an optional version requirement.
+pub fn parse_reference(reference: &str) -> Result<(&str, Option<VersionReq>)> {
+    match reference.split_once('@') {
+        Some((name, requirement)) => {
+            let req = VersionReq::parse(requirement)
+                .with_context(|| format!("Invalid version requirement in '{reference}'"))?;
+            Ok((name, Some(req)))
+        }
+        None => Ok((reference, None)),
+    }
+}
+
+fn version_of(skill: &Skill) -> Version {
+    skill
+        .metadata
+        .semver()
+        .unwrap_or_else(|_| Version::new(0, 0, 0))
+}
+
+/// Resolves a qualified name exactly, or a bare name when only one namespace has it, then
+/// picks the highest version satisfying an optional `@requirement`.
+pub fn find_skill<'a>(skills: &'a [Skill], reference: &str) -> Result<&'a Skill> {
+    let (skill_name, requirement) = parse_reference(reference)?;
+
+    let mut matches = skills
+        .iter()
+        .filter(|s| s.qualified_name() == skill_name)
+        .collect::<Vec<_>>();
+    if matches.is_empty() {
+        matches = skills
+            .iter()
+            .filter(|s| s.matches_name(skill_name))
+            .collect();
+    }
+
+    let mut namespaces = matches
+        .iter()
+        .map(|s| s.qualified_name())
+        .collect::<Vec<_>>();
+    namespaces.sort();
+    namespaces.dedup();
+    if namespaces.len() > 1 {
+        return Err(anyhow!(
+            "Skill name '{skill_name}' is ambiguous, use one of: {}",
+            namespaces.join(", ")
+        ));
+    }
+    if matches.is_empty() {
+        return Err(anyhow!("Skill not found: {skill_name}"));
+    }
+
+    let available = matches
+        .iter()
+        .map(|s| s.metadata.version.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    matches
+        .iter()
+        .copied()
+        .filter(|s| {
+            requirement
+                .as_ref()
+                .is_none_or(|req| req.matches(&version_of(s)))
+        })
+        .max_by_key(|s| version_of(s))
+        .ok_or_else(|| {
+            anyhow!("No version of {skill_name} matches '{reference}' (available: {available})")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_skill, SkillCatalog};
+    use crate::package::signing::TrustConfig;
+    use crate::skill::roots::{RootKind, SkillRoot};
+    use crate::util::testing::{write_skill, TempRoot};
+
+    #[test]
+    fn earlier_roots_shadow_later_ones() {
+        let project = TempRoot::new("catalog-project");
+        let user = TempRoot::new("catalog-user");
+        write_skill(
+            &project.join("commit"),
+            "auto-commit-msg",
+            "project copy",
+            "1.0.0",
+        );
+        write_skill(
+            &user.join("commit"),
+            "auto-commit-msg",
+            "user copy",
+            "1.0.0",
+        );
+        write_skill(&user.join("pr"), "pr-description", "only in user", "1.0.0");
+
+        let roots = vec![
+            SkillRoot {
+                kind: RootKind::Project,
+                path: project.to_path_buf(),
+            },
+            SkillRoot {
+                kind: RootKind::User,
+                path: user.to_path_buf(),
+            },
+        ];
+        let catalog =
+            SkillCatalog::scan(&roots, &TrustConfig::default()).expect("scan should succeed");
+        let active = catalog.active();
+
+        assert_eq!(active.len(), 2);
+        let commit = active
+            .iter()
+            .find(|s| s.metadata.name == "auto-commit-msg")
+            .unwrap();
+        assert_eq!(commit.metadata.description, "project copy");
+
+        let shadowed = catalog
+            .entries
+            .iter()
+            .find(|entry| entry.shadowed_by.is_some())
+            .expect("user copy should be shadowed");
+        assert_eq!(shadowed.root.kind, RootKind::User);
+    }
+
+    #[test]
+    fn project_skill_shadows_a_newer_global_version() {
+        let project = TempRoot::new("catalog-project-low");
+        let user = TempRoot::new("catalog-user-high");
+        write_skill(&project.join("foo"), "foo", "project copy", "1.0.0");
+        write_skill(&user.join("foo-1"), "foo", "user copy", "1.0.0");
+        write_skill(&user.join("foo-2"), "foo", "user upgrade", "2.0.0");
+
+        let roots = vec![
+            SkillRoot {
+                kind: RootKind::Project,
+                path: project.to_path_buf(),
+            },
+            SkillRoot {
+                kind: RootKind::User,
+                path: user.to_path_buf(),
+            },
+        ];
+        let catalog = SkillCatalog::scan(&roots, &TrustConfig::default()).unwrap();
+        let active = catalog.active();
+
+        assert_eq!(active.len(), 1);
+        assert_eq!(
+            find_skill(&active, "foo").unwrap().metadata.description,
+            "project copy"
+        );
+        assert_eq!(
+            catalog
+                .entries
+                .iter()
+                .filter(|entry| entry.shadowed_by.is_some())
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn keeps_versions_side_by_side_and_resolves_requirements() {
+        let root = TempRoot::new("catalog-versions");
+        write_skill(&root.join("commit-v1"), "commit", "old", "1.4.2");
+        write_skill(&root.join("commit-v2"), "commit", "new", "2.0.0");
+        let roots = vec![SkillRoot {
+            kind: RootKind::Cli,
+            path: root.to_path_buf(),
+        }];
+
+        let mut catalog =
+            SkillCatalog::scan(&roots, &TrustConfig::default()).expect("scan should succeed");
+        let skills = catalog.active();
+        assert_eq!(skills.len(), 2);
+        assert_eq!(
+            find_skill(&skills, "commit").unwrap().metadata.version,
+            "2.0.0"
+        );
+        assert_eq!(
+            find_skill(&skills, "commit@^1.2").unwrap().metadata.version,
+            "1.4.2"
+        );
+        assert!(find_skill(&skills, "commit@^3").is_err());
+
+        let pins = [("commit".to_string(), "~1.4".to_string())].into();
+        catalog.apply_pins(&pins).expect("pin should parse");
+        let pinned = catalog.active();
+        assert_eq!(
+            find_skill(&pinned, "commit").unwrap().metadata.version,
+            "1.4.2"
+        );
+    }
+
+    #[test]
+    fn invalid_skills_and_their_callers_are_dropped_as_problems() {
+        let root = TempRoot::new("catalog-invalid");
+        write_skill(&root.join("good"), "good", "fine", "1.0.0");
+        write_skill(&root.join("typo"), "typo", "bad variable", "1.0.0");
+        write_skill(&root.join("caller"), "caller", "calls typo", "1.0.0");
+        let append = |dir: &str, step: &str| {
+            let path = root.join(dir).join("SKILL.md");
+            let content = std::fs::read_to_string(&path).unwrap();
+            std::fs::write(path, format!("{content}\n```genai-step\n{step}```\n")).unwrap();
+        };
+        append(
+            "typo",
+            "id: out\ntype: output\ntemplate: \"{{usr_input}}\"\n",
+        );
+        append("caller", "id: call\ntype: skill\nskill: typo\n");
+        let roots = vec![SkillRoot {
+            kind: RootKind::Cli,
+            path: root.to_path_buf(),
+        }];
+
+        let mut catalog = SkillCatalog::scan(&roots, &TrustConfig::default()).unwrap();
+        catalog.drop_invalid();
+        let names = catalog
+            .active()
+            .iter()
+            .map(|skill| skill.metadata.name.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["good"]);
+        assert_eq!(catalog.problems.len(), 2);
+        assert!(catalog.problems[0].message.contains("usr_input"));
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+use crate::skill::diagnostics::{step, Diagnostic};
+use crate::skill::inputs::BUILTIN_VARS;
+use crate::skill::model::{Skill, StepType, WorkflowStep};
+use crate::util::templating::template_vars;
+
+/// Walks a workflow in step order, tracking which variables each step reads (templates,
+/// `if`, `input_vars`, `with`, routing and judge prompts) and writes (`output_var`,
+/// `outputs`), starting from the built-ins and the declared inputs.
+///
+/// References that would render as empty strings are errors; unused outputs and
+/// `input_vars` that disagree with the prompt are warnings.
+pub fn analyze_dataflow(skill: &Skill) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let steps = &skill.steps;
+    let mut available = BUILTIN_VARS
+        .iter()
+        .map(|name| name.to_string())
+        .chain(skill.metadata.inputs.iter().map(|input| input.name.clone()))
+        .collect::<HashSet<_>>();
+
+    for (i, current) in steps.iter().enumerate() {
+        for (field, var) in reads(current) {
+            if available.contains(&var) {
+                continue;
+            }
+            let producer = steps[i..].iter().find(|later| writes(later).contains(&var));
+            let (code, message) = match producer {
+                Some(producer) if producer.id == current.id => (
+                    "read-before-set",
+                    format!(
+                        "Step '{}' reads '{{{{{var}}}}}' in {field} before it sets it",
+                        current.id
+                    ),
+                ),
+                Some(producer) => (
+                    "read-before-set",
+                    format!(
+                        "Step '{}' reads '{{{{{var}}}}}' in {field} before step '{}' sets it",
+                        current.id, producer.id
+                    ),
+                ),
+                None => (
+                    "undefined-variable",
+                    format!(
+                        "Step '{}' references undefined variable '{{{{{var}}}}}' in {field}{}",
+                        current.id,
+                        suggestion(&var, &available, steps)
+                    ),
+                ),
+            };
+            diagnostics.push(
+                Diagnostic::error(code, step(&current.id, Some(top_level(&field))), message)
+                    .with_needle(needle(&field, &var)),
+            );
+        }
+        available.extend(writes(current));
+
+        if matches!(current.step_type, StepType::Llm) && !current.input_vars.is_empty() {
+            let used = current
+                .prompt
+                .as_deref()
+                .map(template_vars)
+                .unwrap_or_default();
+            for var in current.input_vars.iter().filter(|var| !used.contains(var)) {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        "input-vars-mismatch",
+                        step(&current.id, Some("input_vars")),
+                        format!(
+                            "Step '{}' lists '{var}' in input_vars but its prompt does not use it",
+                            current.id
+                        ),
+                    )
+                    .with_needle(var.as_str()),
+                );
+            }
+            for var in used
+                .iter()
+                .filter(|var| !current.input_vars.contains(var))
+                .filter(|var| !BUILTIN_VARS.contains(&var.as_str()))
+            {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        "input-vars-mismatch",
+                        step(&current.id, Some("prompt")),
+                        format!(
+                            "Step '{}' prompt uses '{{{{{var}}}}}' but input_vars does not list it",
+                            current.id
+                        ),
+                    )
+                    .with_needle(format!("{{{{{var}}}}}")),
+                );
+            }
+        }
+    }
+
+    // The last step's value is the workflow result, so only earlier outputs can go unused.
+    for (i, current) in steps.iter().enumerate().take(steps.len().saturating_sub(1)) {
+        for var in writes(current) {
+            let read_later = steps[i + 1..]
+                .iter()
+                .any(|later| reads(later).iter().any(|(_, read)| *read == var));
+            if !read_later {
+                let field = if current.output_var.as_deref() == Some(var.as_str()) {
+                    "output_var"
+                } else {
+                    "outputs"
+                };
+                diagnostics.push(
+                    Diagnostic::warning(
+                        "unused-output",
+                        step(&current.id, Some(field)),
+                        format!(
+                            "Step '{}' sets '{var}' but no later step reads it",
+                            current.id
+                        ),
+                    )
+                    .with_needle(var.as_str()),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// The step key a read field lives under (`with.diff` is under `with`).
+fn top_level(field: &str) -> &str {
+    field.split('.').next().unwrap_or(field)
+}
+
+/// Text to underline for a read: the `{{var}}` in templates, the bare name elsewhere.
+fn needle(field: &str, var: &str) -> String {
+    match field {
+        "input_vars" | "routing.measure" => var.to_string(),
+        _ => format!("{{{{{var}}}}}"),
+    }
+}
+
+/// `(field, variable)` pairs a step reads before it runs.
+fn reads(step: &WorkflowStep) -> Vec<(String, String)> {
+    let mut reads = Vec::new();
+    let mut add = |field: &str, text: Option<&str>| {
+        for var in text.map(template_vars).unwrap_or_default() {
+            reads.push((field.to_string(), var));
+        }
+    };
+
+    add("if", step.if_expr.as_deref());
+    add("prompt", step.prompt.as_deref());
+    add("template", step.template.as_deref());
+    for (name, value) in &step.with {
+        add(&format!("with.{name}"), Some(value));
+    }
+    if let Some(sampling) = &step.sampling {
+        // `{{candidates}}` is provided by the judge itself.
+        if let Some(judge_prompt) = &sampling.judge_prompt {
+            for var in template_vars(judge_prompt) {
+                if var != "candidates" {
+                    reads.push(("sampling.judge_prompt".to_string(), var));
+                }
+            }
+        }
+    }
+    if let Some(measure) = step.routing.as_ref().and_then(|r| r.measure.as_ref()) {
+        reads.push(("routing.measure".to_string(), measure.clone()));
+    }
+    for var in &step.input_vars {
+        reads.push(("input_vars".to_string(), var.clone()));
+    }
+    reads
+}
+
+fn writes(step: &WorkflowStep) -> Vec<String> {
+    step.output_var
+        .iter()
+        .chain(step.outputs.keys())
+        .cloned()
+        .collect()
+}
+
+/// ` (did you mean '{{x}}'?)` for the closest known name within two edits.
+fn suggestion(var: &str, available: &HashSet<String>, steps: &[WorkflowStep]) -> String {
+    available
+        .iter()
+        .cloned()
+        .chain(steps.iter().flat_map(writes))
+        .map(|name| (edit_distance(var, &name), name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, name)| format!(" (did you mean '{{{{{name}}}}}'?)"))
+        .unwrap_or_default()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::analyze_dataflow;
+    use crate::skill::diagnostics::Severity;
+    use crate::skill::model::Skill;
+    use crate::util::testing::test_skill;
+
+    fn messages(skill: &Skill, severity: Severity) -> Vec<String> {
+        analyze_dataflow(skill)
+            .into_iter()
+            .filter(|d| d.severity == severity)
+            .map(|d| d.message)
+            .collect()
+    }
+
+    fn skill(steps: &str) -> Skill {
+        let mut skill = test_skill("flow");
+        let permissions = &mut skill.metadata.permissions;
+        permissions.run_commands = true;
+        permissions.allowed_runners = vec!["bash".to_string()];
+        permissions.network_access = true;
+        skill.metadata.inputs =
+            vec![serde_yaml::from_str("{ name: since, type: string, default: v1 }").unwrap()];
+        skill.steps = serde_yaml::from_str(steps).unwrap();
+        skill
+    }
+
+    #[test]
+    fn accepts_the_fallback_pattern() {
+        let skill = skill(
+            r#"
+- { id: staged, type: command, runner: bash, cmd: git diff --staged, output_var: diff }
+- { id: unstaged, type: command, runner: bash, cmd: git diff, output_var: diff, if: "{{diff}} == ''" }
+- { id: gen, type: llm, model: m, input_vars: [diff], prompt: "{{diff}} since {{since}}: {{user_input}}", output_var: msg }
+- { id: respond, type: output, template: "{{msg}}" }
+"#,
+        );
+        let errors = messages(&skill, Severity::Error);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(
+            messages(&skill, Severity::Warning),
+            vec!["Step 'gen' prompt uses '{{since}}' but input_vars does not list it"]
+        );
+    }
+
+    #[test]
+    fn reports_typos_order_and_unused_outputs() {
+        let skill = skill(
+            r#"
+- { id: early, type: output, template: "{{summary}}" }
+- { id: diff, type: command, runner: bash, cmd: git diff, output_var: diff }
+- { id: gen, type: llm, model: m, input_vars: [diff, extra], prompt: "{{dif}}", output_var: summary }
+- { id: unused, type: output, template: x, output_var: leftover }
+- { id: respond, type: output, template: done }
+"#,
+        );
+        assert_eq!(
+            messages(&skill, Severity::Error),
+            vec![
+                "Step 'early' reads '{{summary}}' in template before step 'gen' sets it",
+                "Step 'gen' references undefined variable '{{dif}}' in prompt (did you mean '{{diff}}'?)",
+                "Step 'gen' references undefined variable '{{extra}}' in input_vars",
+            ]
+        );
+        let warnings = messages(&skill, Severity::Warning);
+        assert!(warnings
+            .contains(&"Step 'unused' sets 'leftover' but no later step reads it".to_string()));
+        assert!(warnings.contains(
+            &"Step 'gen' lists 'diff' in input_vars but its prompt does not use it".to_string()
+        ));
+    }
+}
@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::skill::format::SkillFormat;
+use crate::skill::model::{Skill, SkillMetadata, WorkflowStep};
+use crate::skill::parser::{
+    delimited_bounds, find_step_fences, frontmatter_bounds, parse_skill_content, uses_inheritance,
+    TOML_DELIMITERS,
+};
+use crate::skill::validator::skill_diagnostics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// What a diagnostic is about, before it is resolved to a position in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Target {
+    Skill,
+    /// A frontmatter key, dotted for nested keys (`permissions.allowed_runners`).
+    Field {
+        path: String,
+    },
+    /// A `genai-step` block, optionally one of its top-level keys.
+    Step {
+        id: String,
+        field: Option<String>,
+    },
+}
+
+/// 1-based position in a file; `end_column` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable kebab-case identifier, e.g. `undefined-variable`.
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    #[serde(skip)]
+    pub target: Target,
+    /// Text to underline within the target, such as `{{dif}}`.
+    #[serde(skip)]
+    pub needle: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, target: Target, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, target, message)
+    }
+
+    pub fn warning(code: &'static str, target: Target, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, target, message)
+    }
+
+    fn new(
+        severity: Severity,
+        code: &'static str,
+        target: Target,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            file: None,
+            span: None,
+            target,
+            needle: None,
+        }
+    }
+
+    pub fn with_needle(mut self, needle: impl Into<String>) -> Self {
+        self.needle = Some(needle.into());
+        self
+    }
+
+    fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub(crate) fn at(mut self, file: &str, span: Option<Span>) -> Self {
+        self.file = Some(file.to_string());
+        self.span = span;
+        self
+    }
+}
+
+pub fn field(path: &str) -> Target {
+    Target::Field {
+        path: path.to_string(),
+    }
+}
+
+pub fn step(id: &str, field: Option<&str>) -> Target {
+    Target::Step {
+        id: id.to_string(),
+        field: field.map(str::to_string),
+    }
+}
+
+/// Parses and validates one skill file, collecting every problem with its position.
+pub fn check_skill_file(path: &Path) -> Vec<Diagnostic> {
+    let file = path.to_string_lossy().to_string();
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            return vec![
+                Diagnostic::error("unreadable", Target::Skill, err.to_string()).at(&file, None),
+            ]
+        }
+    };
+    let source = SourceMap::for_file(path, &content);
+    // Other formats get their syntax errors from parsing below.
+    let markdown = SkillFormat::detect(path, &content) == SkillFormat::Markdown;
+    let (mut diagnostics, invalid) = if markdown {
+        (source.frontmatter_diagnostics(), source.invalid_steps())
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    if diagnostics.is_empty() {
+        // Check the steps that do parse; the broken ones are reported on their own.
+        let blocks = invalid.iter().map(|(block, _)| *block).collect::<Vec<_>>();
+        let checked = if blocks.is_empty() {
+            content.clone()
+        } else {
+            source.without_blocks(&blocks)
+        };
+        match parse_skill_content(path, &checked) {
+            Ok((metadata, markdown_body, steps)) => {
+                let skill = Skill {
+                    metadata,
+                    markdown_body,
+                    steps,
+                    path: file.clone(),
+                    namespace: String::new(),
+                };
+                diagnostics.extend(
+                    skill_diagnostics(&skill)
+                        .into_iter()
+                        .filter(|diagnostic| !maybe_set_by(diagnostic, &blocks)),
+                );
+            }
+            Err(err) => {
+                let target = if uses_inheritance(path, &content) {
+                    field("extends")
+                } else {
+                    Target::Skill
+                };
+                diagnostics.push(Diagnostic::error("unresolved", target, format!("{err:#}")));
+            }
+        }
+    }
+    diagnostics.splice(0..0, invalid.into_iter().map(|(_, diagnostic)| diagnostic));
+
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let span = diagnostic.span.or_else(|| source.locate(&diagnostic));
+            diagnostic.at(&file, span)
+        })
+        .collect()
+}
+
+/// Whether a missing-variable error may only be caused by a skipped block that sets it.
+fn maybe_set_by(diagnostic: &Diagnostic, skipped: &[&StepBlock]) -> bool {
+    if !matches!(diagnostic.code, "undefined-variable" | "read-before-set") {
+        return false;
+    }
+    diagnostic.needle.as_deref().is_some_and(|needle| {
+        let var = needle
+            .trim_start_matches("{{")
+            .trim_end_matches("}}")
+            .trim();
+        skipped.iter().any(|block| block.yaml.contains(var))
+    })
+}
+
+/// Line index of a SKILL.md: where the frontmatter and each step block sit.
+pub(crate) struct SourceMap<'a> {
+    pub(crate) lines: Vec<&'a str>,
+    /// 0-based line indexes of the opening and closing `---`.
+    pub(crate) frontmatter: Option<(usize, usize)>,
+    pub(crate) blocks: Vec<StepBlock>,
+}
+
+pub(crate) struct StepBlock {
+    pub(crate) id: Option<String>,
+    /// 0-based line of the opening fence.
+    pub(crate) fence_line: usize,
+    /// 0-based line indexes of the first and one past the last YAML line.
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) heading: Option<String>,
+    pub(crate) yaml: String,
+}
+
+impl<'a> SourceMap<'a> {
+    pub(crate) fn new(content: &'a str) -> Self {
+        Self::with_frontmatter(content, frontmatter_bounds(content))
+    }
+
+    /// Like `new`, for any skill format. skill.yaml and skill.toml have no step blocks,
+    /// so their problems are reported without a position.
+    pub(crate) fn for_file(path: &Path, content: &'a str) -> Self {
+        match SkillFormat::detect(path, content) {
+            SkillFormat::Markdown => Self::new(content),
+            SkillFormat::MarkdownToml => {
+                Self::with_frontmatter(content, delimited_bounds(content, TOML_DELIMITERS))
+            }
+            SkillFormat::Yaml | SkillFormat::Toml => Self {
+                lines: content.lines().collect(),
+                frontmatter: None,
+                blocks: Vec::new(),
+            },
+        }
+    }
+
+    fn with_frontmatter(content: &'a str, frontmatter: Option<(usize, usize)>) -> Self {
+        let lines = content.lines().collect::<Vec<_>>();
+
+        let body_line = frontmatter.map_or(0, |(_, close)| close + 1);
+        let body = content
+            .split_inclusive('\n')
+            .skip(body_line)
+            .collect::<String>();
+        let id_re = Regex::new(r#"(?m)^id:\s*["']?([^"'\s]+)"#).expect("id pattern is valid");
+        let blocks = find_step_fences(&body)
+            .into_iter()
+            .map(|fence| StepBlock {
+                id: id_re.captures(&fence.yaml).map(|caps| caps[1].to_string()),
+                fence_line: body_line + fence.fence_line,
+                start: body_line + fence.start,
+                end: body_line + fence.end,
+                heading: fence.heading,
+                yaml: fence.yaml,
+            })
+            .collect();
+
+        Self {
+            lines,
+            frontmatter,
+            blocks,
+        }
+    }
+
+    pub(crate) fn syntax_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.frontmatter_diagnostics();
+        diagnostics.extend(
+            self.invalid_steps()
+                .into_iter()
+                .map(|(_, diagnostic)| diagnostic),
+        );
+        diagnostics
+    }
+
+    fn frontmatter_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        match self.frontmatter {
+            None => diagnostics.push(
+                Diagnostic::error(
+                    "missing-frontmatter",
+                    Target::Skill,
+                    "SKILL.md must start with YAML frontmatter between '---' lines",
+                )
+                .with_span(Span::line(1, self.line(0))),
+            ),
+            Some((open, close)) => {
+                let yaml = self.lines[open + 1..close].join("\n");
+                // With `extends`/`include` the metadata is only complete once resolved.
+                let parsed = match serde_yaml::from_str::<serde_yaml::Mapping>(&yaml) {
+                    Ok(mapping)
+                        if mapping.contains_key("extends") || mapping.contains_key("include") =>
+                    {
+                        Ok(())
+                    }
+                    Ok(_) => serde_yaml::from_str::<SkillMetadata>(&yaml).map(drop),
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = parsed {
+                    diagnostics.push(yaml_error("invalid-frontmatter", &err, open + 1, self));
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Step blocks whose YAML is not a valid step, with the error for each.
+    fn invalid_steps(&self) -> Vec<(&StepBlock, Diagnostic)> {
+        self.blocks
+            .iter()
+            .filter_map(|block| {
+                serde_yaml::from_str::<WorkflowStep>(&block.yaml)
+                    .err()
+                    .map(|err| (block, yaml_error("invalid-step", &err, block.start, self)))
+            })
+            .collect()
+    }
+
+    /// `content` with the given blocks, fences included, replaced by blank lines so the
+    /// remaining steps keep their line numbers.
+    fn without_blocks(&self, blocks: &[&StepBlock]) -> String {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let skipped = blocks
+                    .iter()
+                    .any(|block| (block.fence_line..=block.end).contains(&index));
+                if skipped {
+                    "\n".to_string()
+                } else {
+                    format!("{line}\n")
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn line(&self, index: usize) -> &str {
+        self.lines.get(index).copied().unwrap_or_default()
+    }
+
+    /// The block defining step `id`; the last one when the id is repeated.
+    pub(crate) fn block(&self, id: &str) -> Option<&StepBlock> {
+        self.blocks
+            .iter()
+            .rev()
+            .find(|block| block.id.as_deref() == Some(id))
+    }
+
+    /// Resolves a diagnostic's target (and needle) to a span. A step id defined twice
+    /// resolves to its last definition.
+    pub(crate) fn locate(&self, diagnostic: &Diagnostic) -> Option<Span> {
+        let (start, end) = match &diagnostic.target {
+            Target::Skill => match self.frontmatter {
+                Some((open, _)) if diagnostic.needle.is_none() => {
+                    return Some(Span::line(open + 1, self.line(open)))
+                }
+                _ => (0, self.lines.len()),
+            },
+            Target::Field { path } => {
+                let (open, close) = self.frontmatter?;
+                let keys = path.split('.').collect::<Vec<_>>();
+                self.key_region(&keys, open + 1, close)?
+            }
+            Target::Step { id, field } => {
+                let block = self.block(id)?;
+                field
+                    .as_deref()
+                    .and_then(|field| self.key_region(&[field], block.start, block.end))
+                    .unwrap_or((block.start, block.end))
+            }
+        };
+
+        if let Some(needle) = &diagnostic.needle {
+            for index in start..end {
+                if let Some(column) = self.line(index).find(needle.as_str()) {
+                    return Some(Span {
+                        line: index + 1,
+                        column: column + 1,
+                        end_column: column + 1 + needle.len(),
+                    });
+                }
+            }
+        }
+        Some(Span::line(start + 1, self.line(start)))
+    }
+
+    /// Lines `[start, end)` holding a (possibly nested) key, starting at the key's own
+    /// line. Falls back to the outermost key found when a nested key is missing or
+    /// written in flow style (`permissions: { network_access: true }`).
+    pub(crate) fn key_region(
+        &self,
+        keys: &[&str],
+        start: usize,
+        end: usize,
+    ) -> Option<(usize, usize)> {
+        let (key, rest) = keys.split_first()?;
+        let indent = (start..end)
+            .map(|i| self.line(i))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()?;
+        let prefix = format!("{key}:");
+
+        let line = (start..end).find(|&i| {
+            let line = self.line(i);
+            line.len() - line.trim_start().len() == indent && line.trim_start().starts_with(&prefix)
+        })?;
+        let value_end = (line + 1..end)
+            .find(|&i| {
+                let text = self.line(i);
+                !text.trim().is_empty() && text.len() - text.trim_start().len() <= indent
+            })
+            .unwrap_or(end);
+
+        if rest.is_empty() {
+            return Some((line, value_end));
+        }
+        self.key_region(rest, line + 1, value_end)
+            .or(Some((line, value_end)))
+    }
+}
+
+impl Span {
+    /// The whole of a 1-based line, ignoring indentation.
+    fn line(line: usize, text: &str) -> Self {
+        let column = text.len() - text.trim_start().len() + 1;
+        Self {
+            line,
+            column,
+            end_column: text.trim_end().len().max(column) + 1,
+        }
+    }
+}
+
+/// Maps a YAML error inside a block starting at `first_line` to a file position.
+fn yaml_error(
+    code: &'static str,
+    err: &serde_yaml::Error,
+    first_line: usize,
+    source: &SourceMap,
+) -> Diagnostic {
+    let message = err.to_string();
+    let message = message
+        .split(" at line ")
+        .next()
+        .unwrap_or(&message)
+        .to_string();
+    let diagnostic = Diagnostic::error(code, Target::Skill, message);
+    match err.location() {
+        Some(location) => {
+            let line = first_line + location.line();
+            let text = source.line(line - 1);
+            let column = location.column().min(text.len().max(1));
+            diagnostic.with_span(Span {
+                line,
+                column,
+                end_column: text.trim_end().len().max(column) + 1,
+            })
+        }
+        None => diagnostic.with_span(Span::line(first_line + 1, source.line(first_line))),
+    }
+}
+
+/// Annotated snippets, errors first, then a summary line.
+pub fn render_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    let mut sources: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut out = Vec::new();
+
+    let mut sorted = diagnostics.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|d| {
+        (
+            d.file.clone(),
+            d.span.map(|s| (s.line, s.column)),
+            d.severity,
+        )
+    });
+
+    for diagnostic in sorted {
+        let mut lines = vec![format!(
+            "{}[{}]: {}",
+            diagnostic.severity.as_str(),
+            diagnostic.code,
+            diagnostic.message
+        )];
+        if let Some(file) = &diagnostic.file {
+            match diagnostic.span {
+                Some(span) => {
+                    let gutter = " ".repeat(span.line.to_string().len());
+                    lines.push(format!("{gutter}--> {file}:{}:{}", span.line, span.column));
+                    let source = sources.entry(file.as_str()).or_insert_with(|| {
+                        std::fs::read_to_string(file)
+                            .map(|content| content.lines().map(str::to_string).collect())
+                            .unwrap_or_default()
+                    });
+                    if let Some(text) = source.get(span.line - 1) {
+                        let marker = if diagnostic.severity == Severity::Error {
+                            '^'
+                        } else {
+                            '-'
+                        };
+                        lines.push(format!("{gutter} |"));
+                        lines.push(format!("{} | {text}", span.line));
+                        lines.push(format!(
+                            "{gutter} | {}{}",
+                            " ".repeat(span.column - 1),
+                            marker
+                                .to_string()
+                                .repeat(span.end_column.saturating_sub(span.column).max(1))
+                        ));
+                    }
+                }
+                None => lines.push(format!(" --> {file}")),
+            }
+        }
+        out.push(lines.join("\n"));
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    out.push(format!(
+        "{errors} error(s), {} warning(s)",
+        diagnostics.len() - errors
+    ));
+    out.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_skill_file, Severity};
+    use crate::util::testing::TempRoot;
+
+    #[test]
+    fn reports_every_problem_with_its_position() {
+        let dir = TempRoot::new("diagnostics");
+        let path = dir.join("SKILL.md");
+        std::fs::write(
+            &path,
+            "---\nname: flow\ndescription: d\nversion: one\ncategory: test\ntags: []\n\
+entrypoint: workflow\nworkflow_version: 1\n\
+capabilities: { requires_repo: false, supports_interactive: false }\n\
+permissions:\n  run_commands: true\n  allowed_runners: [bash]\n  allowed_paths: []\n  network_access: true\n  write_access: false\n\
+response_format: { type: text }\n---\n# flow\n\n\
+```genai-step\nid: diff\ntype: command\nrunner: zsh\ncmd: git diff\noutput_var: diff\n```\n\n\
+```genai-step\nid: gen\ntype: llm\nmodel: m\nprompt: |\n  Summarize:\n  {{dif}}\noutput_var: summary\n```\n\n\
+```genai-step\nid: notes\ntype: wiki\noutput_var: notes\n```\n\n\
+```genai-step\nid: out\ntype: output\ntemplate: \"{{summary}} {{notes}}\"\n```\n",
+        )
+        .unwrap();
+
+        let diagnostics = check_skill_file(&path);
+        let found = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .map(|d| (d.code, d.span.map(|s| (s.line, s.column))))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("invalid-step", Some((40, 7))),
+                ("invalid-version", Some((4, 1))),
+                ("runner-not-allowed", Some((23, 1))),
+                ("undefined-variable", Some((34, 3))),
+            ]
+        );
+
+        std::fs::write(&path, "---\nname: [unclosed\n---\n").unwrap();
+        let diagnostics = check_skill_file(&path);
+        assert_eq!(diagnostics[0].code, "invalid-frontmatter");
+        assert_eq!(diagnostics[0].span.map(|s| s.line), Some(3));
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::llm::client::LlmClient;
+use crate::skill::model::Skill;
+
+/// Skill embeddings cached on disk, keyed by the skill file's absolute path and
+/// invalidated by its content hash or a change of embedding model. One index can serve
+/// several projects, so skills missing from a catalog keep their entries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingIndex {
+    pub model: String,
+    #[serde(default)]
+    pub entries: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub hash: String,
+    pub vector: Vec<f32>,
+}
+
+impl EmbeddingIndex {
+    /// `~/GenAI/cache/skill-embeddings.json`.
+    pub fn default_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("HOME is not set")?;
+        Ok(PathBuf::from(home)
+            .join("GenAI")
+            .join("cache")
+            .join("skill-embeddings.json"))
+    }
+
+    /// Loads the index; a missing or unreadable file is an empty index.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Embeds skills that are new or changed in one batch and drops entries whose skill
+    /// file no longer exists. Returns how many skills were embedded.
+    pub fn refresh(&mut self, skills: &[Skill], llm: &dyn LlmClient, model: &str) -> Result<usize> {
+        if self.model != model {
+            self.entries.clear();
+            self.model = model.to_string();
+        }
+        self.entries.retain(|path, _| Path::new(path).is_file());
+
+        let mut stale = Vec::new();
+        for skill in skills {
+            let key = entry_key(skill);
+            let hash = skill_hash(skill)?;
+            if self
+                .entries
+                .get(&key)
+                .is_none_or(|entry| entry.hash != hash)
+            {
+                stale.push((key, hash, document_text(skill)));
+            }
+        }
+
+        if !stale.is_empty() {
+            debug!(count = stale.len(), model, "Embedding skills");
+            let texts = stale
+                .iter()
+                .map(|(_, _, text)| text.clone())
+                .collect::<Vec<_>>();
+            let vectors = llm.embed(model, &texts)?;
+            if vectors.len() != texts.len() {
+                return Err(anyhow!(
+                    "Embedding returned {} vectors for {} skills",
+                    vectors.len(),
+                    texts.len()
+                ));
+            }
+            for ((key, hash, _), vector) in stale.iter().zip(vectors) {
+                self.entries.insert(
+                    key.clone(),
+                    IndexEntry {
+                        hash: hash.clone(),
+                        vector,
+                    },
+                );
+            }
+        }
+
+        Ok(stale.len())
+    }
+
+    /// `(skill index, cosine similarity)` for indexed skills, most similar first.
+    pub fn rank(&self, skills: &[Skill], query: &[f32]) -> Vec<(usize, f64)> {
+        let mut ranked = skills
+            .iter()
+            .enumerate()
+            .filter_map(|(i, skill)| {
+                let entry = self.entries.get(&entry_key(skill))?;
+                Some((i, cosine(&entry.vector, query)))
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked
+    }
+}
+
+/// The text embedded for a skill: what it does and what users ask it for.
+pub fn document_text(skill: &Skill) -> String {
+    let metadata = &skill.metadata;
+    let mut text = format!(
+        "{}: {}\nCategory: {}\nTags: {}",
+        skill.qualified_name(),
+        metadata.description,
+        metadata.category,
+        metadata.tags.join(", ")
+    );
+    for example in &metadata.examples {
+        text.push_str("\n- ");
+        text.push_str(example);
+    }
+    text
+}
+
+fn entry_key(skill: &Skill) -> String {
+    let path = Path::new(&skill.path);
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Hash of the skill file's bytes plus the embedded text, which also covers inherited fields.
+fn skill_hash(skill: &Skill) -> Result<String> {
+    let content =
+        std::fs::read(&skill.path).with_context(|| format!("Failed to read {}", skill.path))?;
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    hasher.update([0]);
+    hasher.update(document_text(skill).as_bytes());
+    Ok(format!("sha256:{:x}", hasher.finalize()))
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| *x as f64 * *y as f64)
+        .sum::<f64>();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::Result;
+
+    use super::EmbeddingIndex;
+    use crate::llm::client::LlmClient;
+    use crate::llm::mock::hashed_embedding;
+    use crate::package::signing::TrustConfig;
+    use crate::skill::scanner::scan_skills;
+    use crate::util::testing::{write_skill, TempRoot};
+
+    #[derive(Default)]
+    struct CountingEmbedder(AtomicUsize);
+
+    impl LlmClient for CountingEmbedder {
+        fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+            unreachable!()
+        }
+
+        fn embed(&self, _model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.0.fetch_add(texts.len(), Ordering::SeqCst);
+            Ok(texts.iter().map(|text| hashed_embedding(text)).collect())
+        }
+    }
+
+    #[test]
+    fn embeds_only_changed_skills_and_ranks_by_similarity() {
+        let root = TempRoot::new("embedding");
+        write_skill(
+            &root.join("commit"),
+            "commit",
+            "Write a commit message for staged changes",
+            "1.0.0",
+        );
+        write_skill(
+            &root.join("weather"),
+            "weather",
+            "Report the weather forecast",
+            "1.0.0",
+        );
+        let load = || {
+            scan_skills(&root.to_string_lossy(), &TrustConfig::default())
+                .unwrap()
+                .skills
+        };
+        let index_path = root.join("index.json");
+        let llm = CountingEmbedder::default();
+
+        let skills = load();
+        let mut index = EmbeddingIndex::load(&index_path);
+        assert_eq!(index.refresh(&skills, &llm, "m").unwrap(), 2);
+        index.save(&index_path).unwrap();
+
+        write_skill(
+            &root.join("weather"),
+            "weather",
+            "Report tomorrow's weather forecast",
+            "1.0.0",
+        );
+        let skills = load();
+        let mut index = EmbeddingIndex::load(&index_path);
+        assert_eq!(index.refresh(&skills, &llm, "m").unwrap(), 1);
+        assert_eq!(index.refresh(&skills, &llm, "other").unwrap(), 2);
+        assert_eq!(llm.0.load(Ordering::SeqCst), 5);
+
+        let ranked = index.rank(&skills, &hashed_embedding("what is the weather forecast"));
+        assert_eq!(skills[ranked[0].0].metadata.name, "weather");
+
+        // Skills outside the current catalog keep their vectors until their file is gone.
+        assert_eq!(index.refresh(&skills[..1], &llm, "other").unwrap(), 0);
+        assert_eq!(index.entries.len(), 2);
+        std::fs::remove_dir_all(root.join("weather")).unwrap();
+        index.refresh(&skills[..1], &llm, "other").unwrap();
+        assert_eq!(index.entries.len(), 1);
+    }
+}
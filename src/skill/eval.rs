@@ -0,0 +1,336 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::llm::client::LlmClient;
+use crate::skill::model::Skill;
+use crate::skill::selector::{select_skill, SelectionMethod, SelectorConfig, SelectorMode};
+
+/// Label used in the confusion matrix when no skill was selected.
+const NO_SELECTION: &str = "(none)";
+
+/// One line of an evaluation dataset.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    pub prompt: String,
+    /// Bare or qualified name of the skill the prompt should route to.
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// The selector model over the whole catalog.
+    Llm,
+    /// BM25 only, without calling a model.
+    Lexical,
+    /// Embedding similarity, reranked if the config says so.
+    Semantic,
+}
+
+impl Strategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Llm => "llm",
+            Self::Lexical => "lexical",
+            Self::Semantic => "semantic",
+        }
+    }
+
+    /// Whether a selection made by `method` was made by this strategy rather than a fallback.
+    fn produced_by(self, method: SelectionMethod) -> bool {
+        matches!(
+            (self, method),
+            (Self::Llm, SelectionMethod::Llm)
+                | (Self::Lexical, SelectionMethod::Fallback)
+                | (Self::Semantic, SelectionMethod::Embedding | SelectionMethod::Llm)
+        )
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "llm" => Ok(Self::Llm),
+            "lexical" => Ok(Self::Lexical),
+            "semantic" => Ok(Self::Semantic),
+            other => Err(format!(
+                "unknown strategy '{other}' (expected llm, lexical or semantic)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub prompt: String,
+    pub expected: String,
+    pub selected: Option<String>,
+    pub confidence: Option<f64>,
+    pub method: Option<SelectionMethod>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyReport {
+    pub strategy: Strategy,
+    pub total: usize,
+    pub correct: usize,
+    pub accuracy: f64,
+    /// Mean over selections that reported a confidence.
+    pub mean_confidence: Option<f64>,
+    /// Cases answered by a fallback instead of the strategy itself.
+    pub fallbacks: usize,
+    /// Expected skill -> selected skill -> count.
+    pub confusion: BTreeMap<String, BTreeMap<String, usize>>,
+    pub failures: Vec<CaseResult>,
+}
+
+/// Reads a JSONL dataset of `{"prompt": ..., "expected": ...}` lines; blank lines are skipped.
+pub fn load_dataset(path: &Path) -> Result<Vec<EvalCase>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read dataset {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{}:{}: invalid dataset line", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Routes every case through `select_skill` once per strategy, non-interactively.
+pub fn evaluate(
+    cases: &[EvalCase],
+    skills: &[Skill],
+    llm: &dyn LlmClient,
+    config: &SelectorConfig,
+    strategies: &[Strategy],
+) -> Result<Vec<StrategyReport>> {
+    for case in cases {
+        if !skills.iter().any(|skill| skill.matches_name(&case.expected)) {
+            return Err(anyhow!(
+                "Dataset expects unknown skill '{}' for prompt '{}'",
+                case.expected,
+                case.prompt
+            ));
+        }
+    }
+
+    Ok(strategies
+        .iter()
+        .map(|&strategy| evaluate_strategy(cases, skills, llm, config, strategy))
+        .collect())
+}
+
+fn evaluate_strategy(
+    cases: &[EvalCase],
+    skills: &[Skill],
+    llm: &dyn LlmClient,
+    config: &SelectorConfig,
+    strategy: Strategy,
+) -> StrategyReport {
+    let mut config = config.clone();
+    config.mode = match strategy {
+        Strategy::Semantic => SelectorMode::Embedding,
+        Strategy::Llm | Strategy::Lexical => SelectorMode::Llm,
+    };
+    let client = (strategy != Strategy::Lexical).then_some(llm);
+
+    let mut report = StrategyReport {
+        strategy,
+        total: cases.len(),
+        correct: 0,
+        accuracy: 0.0,
+        mean_confidence: None,
+        fallbacks: 0,
+        confusion: BTreeMap::new(),
+        failures: Vec::new(),
+    };
+    let mut confidences = Vec::new();
+
+    for case in cases {
+        let (result, correct) = match select_skill(&case.prompt, skills, client, &config, false) {
+            Ok(selection) => {
+                confidences.extend(selection.confidence);
+                if !strategy.produced_by(selection.method) {
+                    report.fallbacks += 1;
+                }
+                // Label correct picks with the dataset's spelling so the diagonal lines up.
+                let correct = selection.skill.matches_name(&case.expected);
+                let result = CaseResult {
+                    prompt: case.prompt.clone(),
+                    expected: case.expected.clone(),
+                    selected: Some(if correct {
+                        case.expected.clone()
+                    } else {
+                        selection.skill.qualified_name()
+                    }),
+                    confidence: selection.confidence,
+                    method: Some(selection.method),
+                    error: None,
+                };
+                (result, correct)
+            }
+            Err(err) => {
+                let result = CaseResult {
+                    prompt: case.prompt.clone(),
+                    expected: case.expected.clone(),
+                    selected: None,
+                    confidence: None,
+                    method: None,
+                    error: Some(err.to_string()),
+                };
+                (result, false)
+            }
+        };
+
+        let selected = result.selected.as_deref().unwrap_or(NO_SELECTION);
+        *report
+            .confusion
+            .entry(case.expected.clone())
+            .or_default()
+            .entry(selected.to_string())
+            .or_default() += 1;
+        if correct {
+            report.correct += 1;
+        } else {
+            report.failures.push(result);
+        }
+    }
+
+    if report.total > 0 {
+        report.accuracy = report.correct as f64 / report.total as f64;
+    }
+    if !confidences.is_empty() {
+        report.mean_confidence = Some(confidences.iter().sum::<f64>() / confidences.len() as f64);
+    }
+    report
+}
+
+pub fn render_text(reports: &[StrategyReport]) -> String {
+    let mut sections = Vec::new();
+    for report in reports {
+        let mut lines = vec![format!(
+            "== {}: {}/{} correct ({:.1}%), mean confidence {}, {} fallback(s)",
+            report.strategy.as_str(),
+            report.correct,
+            report.total,
+            report.accuracy * 100.0,
+            report
+                .mean_confidence
+                .map_or_else(|| "n/a".to_string(), |c| format!("{c:.2}")),
+            report.fallbacks
+        )];
+
+        let columns = report
+            .confusion
+            .values()
+            .flat_map(|row| row.keys())
+            .chain(report.confusion.keys())
+            .collect::<BTreeSet<_>>();
+        let width = columns.iter().map(|c| c.len()).max().unwrap_or(0).max(8);
+        lines.push(format!(
+            "{:<width$}  {}",
+            "EXPECTED",
+            columns
+                .iter()
+                .map(|c| format!("{c:>width$}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ));
+        for (expected, row) in &report.confusion {
+            lines.push(format!(
+                "{expected:<width$}  {}",
+                columns
+                    .iter()
+                    .map(|c| format!("{:>width$}", row.get(*c).copied().unwrap_or(0)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+
+        for failure in &report.failures {
+            let outcome = match (&failure.selected, &failure.error) {
+                (_, Some(error)) => format!("error: {error}"),
+                (Some(selected), None) => format!("got {selected}"),
+                (None, None) => String::new(),
+            };
+            lines.push(format!(
+                "FAIL '{}': expected {}, {outcome}",
+                failure.prompt, failure.expected
+            ));
+        }
+        sections.push(lines.join("\n"));
+    }
+    sections.join("\n\n")
+}
+
+pub fn render_json(reports: &[StrategyReport]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(reports)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, EvalCase, Strategy};
+    use crate::llm::mock::MockLlmClient;
+    use crate::skill::model::Skill;
+    use crate::skill::selector::SelectorConfig;
+    use crate::util::testing::test_skill;
+
+    fn skill(name: &str, description: &str) -> Skill {
+        let mut skill = test_skill(name);
+        skill.metadata.description = description.to_string();
+        skill
+    }
+
+    #[test]
+    fn reports_accuracy_confusion_and_failures() {
+        let skills = vec![
+            skill("commit", "Write a commit message for staged changes"),
+            skill("changelog", "Draft release notes from the git history"),
+        ];
+        let case = |prompt: &str, expected: &str| EvalCase {
+            prompt: prompt.to_string(),
+            expected: expected.to_string(),
+        };
+        let cases = vec![
+            case("write a commit message", "commit"),
+            case("draft the release notes", "changelog"),
+            case("write the commit log", "changelog"),
+            case("order a pizza", "commit"),
+        ];
+
+        let reports = evaluate(
+            &cases,
+            &skills,
+            &MockLlmClient::new(),
+            &SelectorConfig::default(),
+            &[Strategy::Lexical],
+        )
+        .unwrap();
+        let report = &reports[0];
+        assert_eq!((report.correct, report.total), (2, 4));
+        assert_eq!(report.confusion["changelog"]["commit"], 1);
+        assert_eq!(report.confusion["commit"]["(none)"], 1);
+        assert_eq!(report.failures.len(), 2);
+        assert!(report.failures[1].error.is_some());
+
+        let unknown = vec![case("anything", "missing")];
+        assert!(evaluate(
+            &unknown,
+            &skills,
+            &MockLlmClient::new(),
+            &SelectorConfig::default(),
+            &[Strategy::Lexical]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use serde_yaml::{Mapping, Value};
+
+use crate::skill::model::WorkflowStep;
+use crate::skill::parser::{
+    find_step_fences, parse_genai_steps, prune, split_delimited, strip_step_sections,
+    TOML_DELIMITERS, YAML_DELIMITERS,
+};
+
+/// Skill file names, in the order used when a directory has more than one.
+pub const SKILL_FILE_NAMES: [&str; 3] = ["SKILL.md", "skill.yaml", "skill.toml"];
+
+/// Ways a skill can be written. All of them map to the same `Skill`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillFormat {
+    /// SKILL.md with `---` YAML frontmatter and `genai-step` blocks.
+    Markdown,
+    /// SKILL.md with `+++` TOML frontmatter.
+    MarkdownToml,
+    /// skill.yaml: the frontmatter keys plus `steps` and an optional `body`.
+    Yaml,
+    /// skill.toml: the same document as skill.yaml, in TOML.
+    Toml,
+}
+
+impl SkillFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::MarkdownToml => "md-toml",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Markdown | Self::MarkdownToml => "SKILL.md",
+            Self::Yaml => "skill.yaml",
+            Self::Toml => "skill.toml",
+        }
+    }
+
+    /// By extension, then for Markdown by the opening frontmatter delimiter.
+    pub fn detect(path: &Path, content: &str) -> Self {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("toml") => Self::Toml,
+            _ if content
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .is_some_and(|line| line.trim_start_matches('\u{feff}').trim_end() == "+++") =>
+            {
+                Self::MarkdownToml
+            }
+            _ => Self::Markdown,
+        }
+    }
+
+    fn is_markdown(self) -> bool {
+        matches!(self, Self::Markdown | Self::MarkdownToml)
+    }
+}
+
+impl FromStr for SkillFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "md-toml" => Ok(Self::MarkdownToml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            other => Err(anyhow!(
+                "Unknown skill format '{other}' (expected md, md-toml, yaml or toml)"
+            )),
+        }
+    }
+}
+
+pub fn is_skill_file(name: &OsStr) -> bool {
+    SKILL_FILE_NAMES.iter().any(|candidate| name == *candidate)
+}
+
+/// The skill file in `dir`, preferring SKILL.md, then skill.yaml, then skill.toml.
+pub fn find_skill_file(dir: &Path) -> Option<PathBuf> {
+    SKILL_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// One skill file as written: `extends` and `include` are not applied.
+#[derive(Debug, Clone)]
+pub struct SkillDocument {
+    pub frontmatter: Mapping,
+    /// The Markdown body; for skill.yaml and skill.toml, the optional `body` value.
+    pub body: String,
+    pub steps: Vec<WorkflowStep>,
+}
+
+pub fn read_document(content: &str, format: SkillFormat) -> Result<SkillDocument> {
+    match format {
+        SkillFormat::Markdown | SkillFormat::MarkdownToml => {
+            let (frontmatter, body) = if format == SkillFormat::Markdown {
+                let (yaml, body) = split_delimited(content, YAML_DELIMITERS, "YAML")?;
+                let frontmatter =
+                    serde_yaml::from_str(yaml).context("Failed to parse frontmatter YAML")?;
+                (frontmatter, body)
+            } else {
+                let (toml, body) = split_delimited(content, TOML_DELIMITERS, "TOML")?;
+                (
+                    toml_mapping(toml).context("Failed to parse frontmatter TOML")?,
+                    body,
+                )
+            };
+            let body_line = content[..content.len() - body.len()].lines().count();
+            let mut steps = parse_genai_steps(body)?;
+            for source in steps.iter_mut().filter_map(|step| step.source.as_mut()) {
+                source.line += body_line;
+            }
+            Ok(SkillDocument {
+                frontmatter,
+                body: body.to_string(),
+                steps,
+            })
+        }
+        SkillFormat::Yaml | SkillFormat::Toml => {
+            let mut document: Mapping = if format == SkillFormat::Yaml {
+                serde_yaml::from_str(content).context("Failed to parse skill YAML")?
+            } else {
+                toml_mapping(content).context("Failed to parse skill TOML")?
+            };
+            let steps = match document.remove("steps") {
+                Some(steps) => serde_yaml::from_value(steps).context("Failed to parse steps")?,
+                None => Vec::new(),
+            };
+            let body = match document.remove("body") {
+                Some(Value::String(body)) => body,
+                Some(_) => return Err(anyhow!("body must be a string")),
+                None => String::new(),
+            };
+            Ok(SkillDocument {
+                frontmatter: document,
+                body,
+                steps,
+            })
+        }
+    }
+}
+
+fn toml_mapping(text: &str) -> Result<Mapping> {
+    Ok(toml::from_str(text)?)
+}
+
+/// Writes `document` in `format`. `body` is treated as documentation only: steps are
+/// always written from `steps`, after it in Markdown formats.
+pub fn render_document(document: &SkillDocument, format: SkillFormat) -> Result<String> {
+    let frontmatter = prune(Value::Mapping(document.frontmatter.clone()), false);
+    let body = document.body.trim();
+
+    if !format.is_markdown() {
+        let mut out = match frontmatter {
+            Value::Mapping(mapping) => mapping,
+            _ => Mapping::new(),
+        };
+        if !body.is_empty() {
+            out.insert("body".into(), body.into());
+        }
+        if !document.steps.is_empty() {
+            let steps = document
+                .steps
+                .iter()
+                .map(|step| Ok(prune(serde_yaml::to_value(step)?, true)))
+                .collect::<Result<Vec<_>>>()?;
+            out.insert("steps".into(), Value::Sequence(steps));
+        }
+        return match format {
+            SkillFormat::Yaml => Ok(serde_yaml::to_string(&out)?),
+            _ => Ok(toml::to_string(&out)?),
+        };
+    }
+
+    let mut out = if format == SkillFormat::Markdown {
+        format!("---\n{}---\n\n", serde_yaml::to_string(&frontmatter)?)
+    } else {
+        format!("+++\n{}+++\n\n", toml::to_string(&frontmatter)?)
+    };
+    if !body.is_empty() {
+        out.push_str(&format!("{body}\n\n"));
+    }
+    for step in &document.steps {
+        let mut step = step.clone();
+        out.push_str(&format!("### Step: {}\n\n", step.id));
+        // A description goes under the heading only if parsing it back gives the same text.
+        if let Some(description) = step.description.clone() {
+            if prose_round_trips(&step.id, &description) {
+                out.push_str(&format!("{description}\n\n"));
+                step.description = None;
+            }
+        }
+        out.push_str(&format!(
+            "```genai-step\n{}```\n\n",
+            serde_yaml::to_string(&prune(serde_yaml::to_value(&step)?, true))?
+        ));
+    }
+    Ok(out.trim_end().to_string() + "\n")
+}
+
+fn prose_round_trips(id: &str, description: &str) -> bool {
+    let snippet = format!("### Step: {id}\n\n{description}\n\n```genai-step\nid: {id}\n```\n");
+    find_step_fences(&snippet)
+        .first()
+        .is_some_and(|fence| fence.description(id).as_deref() == Some(description))
+}
+
+/// Rewrites a skill file in another format. Fails rather than drop anything: the result
+/// is parsed back and must give the same frontmatter, steps and documentation.
+pub fn convert(content: &str, from: SkillFormat, to: SkillFormat) -> Result<String> {
+    let source = documentation_only(read_document(content, from)?, from);
+    let rendered = render_document(&source, to)?;
+    let converted = read_document(&rendered, to)
+        .with_context(|| format!("Converted {} does not parse", to.file_name()))?;
+    let converted = documentation_only(converted, to);
+
+    let (before, after) = (normalize(&source)?, normalize(&converted)?);
+    if before.0 != after.0 {
+        return Err(anyhow!(
+            "Converting to {} would change the frontmatter",
+            to.as_str()
+        ));
+    }
+    if before.1 != after.1 {
+        return Err(anyhow!(
+            "Converting to {} would change the steps",
+            to.as_str()
+        ));
+    }
+    if before.2 != after.2 {
+        return Err(anyhow!(
+            "Converting to {} would change the documentation",
+            to.as_str()
+        ));
+    }
+    Ok(rendered)
+}
+
+/// Markdown bodies repeat the steps; keep only the prose that belongs to no step.
+fn documentation_only(mut document: SkillDocument, format: SkillFormat) -> SkillDocument {
+    if format.is_markdown() {
+        document.body = strip_step_sections(&document.body);
+    }
+    document
+}
+
+fn normalize(document: &SkillDocument) -> Result<(Value, Value, String)> {
+    Ok((
+        prune(Value::Mapping(document.frontmatter.clone()), false),
+        prune(serde_yaml::to_value(&document.steps)?, true),
+        document.body.trim().to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, read_document, SkillFormat};
+
+    const SKILL_MD: &str = "---\nname: notes\ndescription: Release notes\nversion: 1.2.0\ncategory: docs\n\
+tags: [docs]\nentrypoint: workflow\nworkflow_version: 1\n\
+capabilities: { requires_repo: true, supports_interactive: false }\n\
+permissions: { run_commands: true, allowed_runners: [bash], allowed_paths: [], network_access: true, write_access: false }\n\
+response_format: { type: markdown }\ninputs:\n  - { name: limit, type: int, default: 20 }\n---\n\n\
+# Overview\n\nWrites notes.\n\n- terse\n- grouped\n\n\
+### Step: Read history\n\nOne line per commit.\n\n\
+```genai-step\nid: log\ntype: command\nrunner: bash\ncmd: git log -n \"$GENAI_INPUT_LIMIT\"\noutput_var: log\n```\n\n\
+```genai-step\nid: write\ntype: llm\nmodel: m\ndescription: \"two\\nlines\"\nprompt: |\n  Notes for:\n  {{log}}\noutput_var: notes\n```\n";
+
+    #[test]
+    fn converts_between_every_format_without_loss() {
+        let original = read_document(SKILL_MD, SkillFormat::Markdown).unwrap();
+        assert_eq!(
+            original.steps[0].description.as_deref(),
+            Some("Read history\n\nOne line per commit.")
+        );
+
+        let formats = [
+            SkillFormat::Yaml,
+            SkillFormat::Toml,
+            SkillFormat::MarkdownToml,
+            SkillFormat::Markdown,
+        ];
+        let mut content = SKILL_MD.to_string();
+        let mut from = SkillFormat::Markdown;
+        for to in formats {
+            content = convert(&content, from, to).unwrap_or_else(|err| panic!("{to:?}: {err:#}"));
+            from = to;
+        }
+
+        let round_trip = read_document(&content, SkillFormat::Markdown).unwrap();
+        assert_eq!(round_trip.frontmatter, original.frontmatter);
+        assert_eq!(round_trip.steps.len(), 2);
+        assert_eq!(
+            round_trip.steps[0].description,
+            original.steps[0].description
+        );
+        assert_eq!(
+            round_trip.steps[1].description.as_deref(),
+            Some("two\nlines")
+        );
+        assert!(content.contains("# Overview\n\nWrites notes.\n\n- terse\n- grouped\n"));
+        assert_eq!(
+            SkillFormat::detect("x/SKILL.md".as_ref(), "+++\nname = 'x'\n+++\n"),
+            SkillFormat::MarkdownToml
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Component, Path};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::skill::model::{InputSpec, InputType, InputValue};
+
+/// Variables every run defines, which declared inputs may not reuse.
+pub const BUILTIN_VARS: &[&str] = &["user_input", "debug", "interactive"];
+
+/// Asks the user for a missing required input.
+pub type InputPrompt<'a> = &'a mut dyn FnMut(&InputSpec) -> Result<String>;
+
+/// Parses repeated `key=value` arguments.
+pub fn parse_assignments(assignments: &[String]) -> Result<BTreeMap<String, String>> {
+    assignments
+        .iter()
+        .map(|assignment| {
+            let (key, value) = assignment
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid --set '{assignment}', expected key=value"))?;
+            Ok((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Reads a YAML or JSON mapping of input values; lists become comma-separated strings.
+pub fn read_input_file(path: &str) -> Result<BTreeMap<String, String>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    let values: BTreeMap<String, InputValue> = serde_yaml::from_str(&content)
+        .with_context(|| format!("Input file {path} must be a mapping of name: value"))?;
+    Ok(values
+        .into_iter()
+        .map(|(key, value)| (key, value.to_raw()))
+        .collect())
+}
+
+/// Applies provided values, then defaults, then `prompt` for missing required inputs, and
+/// checks every value against its declared type. Returns normalized values by name.
+pub fn resolve_inputs(
+    specs: &[InputSpec],
+    provided: &BTreeMap<String, String>,
+    mut prompt: Option<InputPrompt<'_>>,
+) -> Result<BTreeMap<String, String>> {
+    if let Some(unknown) = provided
+        .keys()
+        .find(|key| !specs.iter().any(|spec| &spec.name == *key))
+    {
+        let declared = specs
+            .iter()
+            .map(|spec| spec.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(anyhow!(
+            "Unknown input '{unknown}' (declared: {})",
+            if declared.is_empty() {
+                "none"
+            } else {
+                &declared
+            }
+        ));
+    }
+
+    let mut resolved = BTreeMap::new();
+    for spec in specs {
+        let raw = match (provided.get(&spec.name), &spec.default) {
+            (Some(value), _) => Some(value.clone()),
+            (None, Some(default)) => Some(default.to_raw()),
+            (None, None) if spec.required => match prompt.as_mut() {
+                Some(prompt) => Some(prompt(spec)?),
+                None => {
+                    return Err(anyhow!(
+                        "Missing required input '{}'{}",
+                        spec.name,
+                        spec.description
+                            .as_deref()
+                            .map(|d| format!(": {d}"))
+                            .unwrap_or_default()
+                    ))
+                }
+            },
+            (None, None) => None,
+        };
+
+        let value = match raw {
+            Some(raw) => normalize(spec, &raw)?,
+            None => String::new(),
+        };
+        resolved.insert(spec.name.clone(), value);
+    }
+
+    Ok(resolved)
+}
+
+/// Path inputs must be relative, stay out of `..`, and fall under one of the skill's
+/// `allowed_paths` (`.` allows the whole working directory).
+pub fn check_path_inputs(
+    specs: &[InputSpec],
+    values: &BTreeMap<String, String>,
+    allowed_paths: &[String],
+) -> Result<()> {
+    let allowed = allowed_paths
+        .iter()
+        .filter_map(|path| relative_parts(path))
+        .collect::<Vec<_>>();
+    for spec in specs
+        .iter()
+        .filter(|spec| spec.input_type == InputType::Path)
+    {
+        let value = values.get(&spec.name).map_or("", String::as_str);
+        if value.is_empty() {
+            continue;
+        }
+        let inside = relative_parts(value)
+            .is_some_and(|parts| allowed.iter().any(|prefix| parts.starts_with(prefix)));
+        if !inside {
+            return Err(anyhow!(
+                "Input '{}' is '{value}', which is outside allowed_paths [{}]",
+                spec.name,
+                allowed_paths.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Environment variable through which command steps see the input `name`.
+pub fn input_env_name(name: &str) -> String {
+    format!("GENAI_INPUT_{}", name.to_uppercase().replace('-', "_"))
+}
+
+/// Normal components of a relative path, or `None` if it is absolute or uses `..`.
+fn relative_parts(path: &str) -> Option<Vec<&OsStr>> {
+    let mut parts = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(parts)
+}
+
+/// Checks `raw` against the spec's type and returns its canonical string form.
+pub fn normalize(spec: &InputSpec, raw: &str) -> Result<String> {
+    let value = raw.trim();
+    match spec.input_type {
+        InputType::String | InputType::Path => Ok(raw.to_string()),
+        InputType::Int => value
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| anyhow!("Input '{}' must be an integer, got '{value}'", spec.name)),
+        InputType::Bool => match value.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Ok("true".to_string()),
+            "false" | "no" | "n" | "0" => Ok("false".to_string()),
+            _ => Err(anyhow!(
+                "Input '{}' must be true or false, got '{value}'",
+                spec.name
+            )),
+        },
+        InputType::Enum => {
+            if spec.values.iter().any(|allowed| allowed == value) {
+                Ok(value.to_string())
+            } else {
+                Err(anyhow!(
+                    "Input '{}' must be one of [{}], got '{value}'",
+                    spec.name,
+                    spec.values.join(", ")
+                ))
+            }
+        }
+        InputType::List => Ok(value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .collect::<Vec<_>>()
+            .join(",")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{check_path_inputs, parse_assignments, resolve_inputs};
+    use crate::skill::model::{InputSpec, InputType, InputValue};
+
+    fn spec(name: &str, input_type: InputType) -> InputSpec {
+        InputSpec {
+            name: name.to_string(),
+            input_type,
+            default: None,
+            required: false,
+            description: None,
+            values: vec![],
+        }
+    }
+
+    #[test]
+    fn applies_defaults_and_normalizes_types() {
+        let mut since = spec("since", InputType::String);
+        since.required = true;
+        let mut limit = spec("limit", InputType::Int);
+        limit.default = Some(InputValue::Int(20));
+        let mut format = spec("format", InputType::Enum);
+        format.values = vec!["md".to_string(), "json".to_string()];
+        format.default = Some(InputValue::String("md".to_string()));
+        let draft = spec("draft", InputType::Bool);
+
+        let provided = parse_assignments(&["since=v1.2.0".to_string(), "draft=yes".to_string()])
+            .expect("assignments should parse");
+        let values = resolve_inputs(&[since, limit, format, draft], &provided, None)
+            .expect("inputs should resolve");
+
+        assert_eq!(values["since"], "v1.2.0");
+        assert_eq!(values["limit"], "20");
+        assert_eq!(values["format"], "md");
+        assert_eq!(values["draft"], "true");
+    }
+
+    #[test]
+    fn rejects_bad_values_unknown_names_and_missing_required() {
+        let mut format = spec("format", InputType::Enum);
+        format.values = vec!["md".to_string()];
+        let provided = BTreeMap::from([("format".to_string(), "html".to_string())]);
+        assert!(resolve_inputs(std::slice::from_ref(&format), &provided, None).is_err());
+
+        let provided = BTreeMap::from([("typo".to_string(), "x".to_string())]);
+        assert!(resolve_inputs(std::slice::from_ref(&format), &provided, None).is_err());
+
+        let mut since = spec("since", InputType::String);
+        since.required = true;
+        assert!(resolve_inputs(std::slice::from_ref(&since), &BTreeMap::new(), None).is_err());
+
+        let mut prompt = |_: &InputSpec| Ok("v2.0.0".to_string());
+        let values = resolve_inputs(&[since], &BTreeMap::new(), Some(&mut prompt)).unwrap();
+        assert_eq!(values["since"], "v2.0.0");
+    }
+
+    #[test]
+    fn path_inputs_stay_inside_allowed_paths() {
+        let specs = [spec("file", InputType::Path)];
+        let allowed = ["docs/".to_string()];
+        let check = |value: &str| {
+            let values = BTreeMap::from([("file".to_string(), value.to_string())]);
+            check_path_inputs(&specs, &values, &allowed)
+        };
+
+        assert!(check("docs/notes.md").is_ok());
+        assert!(check("./docs").is_ok());
+        assert!(check("").is_ok());
+        assert!(check("src/main.rs").is_err());
+        assert!(check("docs/../secrets").is_err());
+        assert!(check("/etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::skill::model::Skill;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Name and tag tokens count this many times, so they outweigh prose.
+const KEYWORD_WEIGHT: usize = 2;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "do", "for", "from", "how", "in", "is", "it",
+    "me", "my", "of", "on", "or", "please", "the", "this", "to", "we", "what", "with", "you",
+];
+
+/// BM25 index over each skill's name, description, category, tags and examples.
+#[derive(Debug, Clone)]
+pub struct LexicalIndex {
+    documents: Vec<HashMap<String, usize>>,
+    lengths: Vec<usize>,
+    average_length: f64,
+    document_frequency: HashMap<String, usize>,
+}
+
+impl LexicalIndex {
+    pub fn new(skills: &[Skill]) -> Self {
+        let documents = skills.iter().map(skill_terms).collect::<Vec<_>>();
+        let lengths = documents
+            .iter()
+            .map(|terms| terms.values().sum())
+            .collect::<Vec<usize>>();
+        let average_length = if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<usize>() as f64 / lengths.len() as f64
+        };
+
+        let mut document_frequency = HashMap::new();
+        for terms in &documents {
+            for term in terms.keys() {
+                *document_frequency.entry(term.clone()).or_insert(0) += 1;
+            }
+        }
+
+        Self {
+            documents,
+            lengths,
+            average_length,
+            document_frequency,
+        }
+    }
+
+    /// `(skill index, score)` for every skill with a positive score, best first.
+    pub fn rank(&self, query: &str) -> Vec<(usize, f64)> {
+        let mut query_terms = tokenize(query);
+        query_terms.sort();
+        query_terms.dedup();
+
+        let mut scores = self
+            .documents
+            .iter()
+            .enumerate()
+            .map(|(i, terms)| {
+                let score = query_terms
+                    .iter()
+                    .map(|term| self.term_score(term, terms, self.lengths[i]))
+                    .sum::<f64>();
+                (i, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scores
+    }
+
+    fn term_score(&self, term: &str, terms: &HashMap<String, usize>, length: usize) -> f64 {
+        let Some(&tf) = terms.get(term) else {
+            return 0.0;
+        };
+        let n = self.documents.len() as f64;
+        let df = self.document_frequency[term] as f64;
+        let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+        let tf = tf as f64;
+        let norm = 1.0 - B + B * length as f64 / self.average_length.max(1.0);
+        idf * tf * (K1 + 1.0) / (tf + K1 * norm)
+    }
+}
+
+fn skill_terms(skill: &Skill) -> HashMap<String, usize> {
+    let metadata = &skill.metadata;
+    let mut terms = HashMap::new();
+    let mut add = |text: &str, weight: usize| {
+        for token in tokenize(text) {
+            *terms.entry(token).or_insert(0) += weight;
+        }
+    };
+
+    add(&skill.qualified_name(), KEYWORD_WEIGHT);
+    for tag in &metadata.tags {
+        add(tag, KEYWORD_WEIGHT);
+    }
+    add(&metadata.category, 1);
+    add(&metadata.description, 1);
+    for example in &metadata.examples {
+        add(example, 1);
+    }
+    terms
+}
+
+/// Lowercased alphanumeric words without stopwords, with a plural `s` stripped.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if stem.len() > 2 && !stem.ends_with('s') => stem.to_string(),
+            _ => word,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, LexicalIndex};
+    use crate::skill::model::Skill;
+    use crate::util::testing::test_skill;
+
+    fn skill(name: &str, description: &str, tags: &[&str], examples: &[&str]) -> Skill {
+        let mut skill = test_skill(name);
+        skill.metadata.category = "git".to_string();
+        skill.metadata.description = description.to_string();
+        skill.metadata.tags = tags.iter().map(|t| t.to_string()).collect();
+        skill.metadata.examples = examples.iter().map(|e| e.to_string()).collect();
+        skill
+    }
+
+    #[test]
+    fn tokenizes_and_strips_plurals() {
+        assert_eq!(
+            tokenize("Summarize the staged-changes, please!"),
+            vec!["summarize", "staged", "change"]
+        );
+    }
+
+    #[test]
+    fn ranks_by_examples_and_tags_over_shared_words() {
+        let skills = vec![
+            skill(
+                "auto-commit-msg",
+                "Generate a commit message from staged changes",
+                &["commit"],
+                &["write a commit message for my changes"],
+            ),
+            skill(
+                "changelog",
+                "Summarize changes between two git tags",
+                &["release", "notes"],
+                &["draft release notes since the last tag"],
+            ),
+        ];
+        let index = LexicalIndex::new(&skills);
+
+        let ranked = index.rank("what changed since the last release?");
+        assert_eq!(ranked[0].0, 1);
+        let ranked = index.rank("I need a commit message");
+        assert_eq!(ranked[0].0, 0);
+        assert!(index.rank("translate this into French").is_empty());
+    }
+}
@@ -0,0 +1,635 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::skill::diagnostics::{field, step, Diagnostic, Severity, SourceMap, Target};
+use crate::skill::format::SkillFormat;
+use crate::skill::model::{InputType, Skill, StepType};
+use crate::skill::parser::parse_skill_file;
+use crate::util::templating::template_vars;
+
+/// Marker for inline suppression: `# genai-lint: allow rule-a, rule-b` in YAML or
+/// `<!-- genai-lint: allow rule-a -->` in the Markdown body.
+const ALLOW_MARKER: &str = "genai-lint: allow";
+
+/// Longest chain of `--fix` edits applied to one file.
+const MAX_FIXES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Error,
+}
+
+/// `lint.rules` in the config file: rule code to level, overriding each rule's default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    pub rules: BTreeMap<String, LintLevel>,
+}
+
+impl LintLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl LintConfig {
+    fn level(&self, rule: &Rule) -> LintLevel {
+        self.rules.get(rule.code).copied().unwrap_or(rule.default)
+    }
+
+    pub fn check_rule_names(&self) -> Result<()> {
+        match self
+            .rules
+            .keys()
+            .find(|name| !RULES.iter().any(|rule| rule.code == name.as_str()))
+        {
+            Some(name) => Err(anyhow!(
+                "Unknown lint rule '{name}'; known rules: {}",
+                RULES
+                    .iter()
+                    .map(|rule| rule.code)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A mechanical source edit that resolves a lint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    /// Sets a boolean key (found via `target`) to `false`.
+    SetFalse { target: Target, key: String },
+    /// Removes `item` from the list under `target`.
+    RemoveListItem { target: Target, item: String },
+    /// Adds a `### Step: <id>` heading above the step's fence.
+    InsertHeading { id: String },
+    /// Appends an output step rendering `{{var}}`.
+    AppendOutputStep { var: String, format: String },
+}
+
+/// What a rule reports: where, why, and how to fix it if that is mechanical.
+type Finding = (Target, String, Option<Fix>);
+
+#[derive(Debug, Clone)]
+pub struct Lint {
+    pub diagnostic: Diagnostic,
+    pub fix: Option<Fix>,
+}
+
+pub struct Rule {
+    pub code: &'static str,
+    pub default: LintLevel,
+    pub summary: &'static str,
+    check: fn(&Skill, &SourceMap) -> Vec<Finding>,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        code: "missing-tags",
+        default: LintLevel::Warn,
+        summary: "skill declares no tags",
+        check: missing_tags,
+    },
+    Rule {
+        code: "missing-examples",
+        default: LintLevel::Warn,
+        summary: "skill declares no example requests for selection",
+        check: missing_examples,
+    },
+    Rule {
+        code: "unused-input-var",
+        default: LintLevel::Warn,
+        summary: "llm prompt does not use a variable listed in input_vars",
+        check: unused_input_var,
+    },
+    Rule {
+        code: "unused-allowed-path",
+        default: LintLevel::Warn,
+        summary: "allowed_paths entry no command step mentions",
+        check: unused_allowed_path,
+    },
+    Rule {
+        code: "unused-network-access",
+        default: LintLevel::Warn,
+        summary: "network_access is true but no step calls a model",
+        check: unused_network_access,
+    },
+    Rule {
+        code: "broad-runner",
+        default: LintLevel::Warn,
+        summary: "allowed_runners entry no command step uses",
+        check: broad_runner,
+    },
+    Rule {
+        code: "step-without-heading",
+        default: LintLevel::Warn,
+        summary: "genai-step block has no Markdown heading above it",
+        check: step_without_heading,
+    },
+    Rule {
+        code: "missing-output-step",
+        default: LintLevel::Warn,
+        summary: "response_format is declared but no output step produces it",
+        check: missing_output_step,
+    },
+];
+
+/// Runs every enabled rule over one SKILL.md. Lints are positioned like `genai check`
+/// diagnostics and exclude anything suppressed inline.
+pub fn lint_skill_file(path: &Path, config: &LintConfig) -> Result<Vec<Lint>> {
+    let file = path.to_string_lossy().to_string();
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let (metadata, markdown_body, steps) = parse_skill_file(path)
+        .with_context(|| format!("Cannot lint {file}; run `genai check` for details"))?;
+    let skill = Skill {
+        metadata,
+        markdown_body,
+        steps,
+        path: file.clone(),
+        namespace: String::new(),
+    };
+    let source = SourceMap::for_file(path, &content);
+    // Fixes edit YAML frontmatter and step blocks in place.
+    let fixable = SkillFormat::detect(path, &content) == SkillFormat::Markdown;
+
+    let mut lints = Vec::new();
+    for rule in RULES {
+        let severity = match config.level(rule) {
+            LintLevel::Allow => continue,
+            LintLevel::Warn => Severity::Warning,
+            LintLevel::Error => Severity::Error,
+        };
+        for (target, message, fix) in (rule.check)(&skill, &source) {
+            if is_suppressed(&source, &target, rule.code) {
+                continue;
+            }
+            let mut diagnostic = match severity {
+                Severity::Error => Diagnostic::error(rule.code, target, message),
+                Severity::Warning => Diagnostic::warning(rule.code, target, message),
+            };
+            if let Some(Fix::RemoveListItem { item, .. }) = &fix {
+                diagnostic = diagnostic.with_needle(item.as_str());
+            }
+            let span = source.locate(&diagnostic);
+            lints.push(Lint {
+                diagnostic: diagnostic.at(&file, span),
+                fix: fix.filter(|_| fixable),
+            });
+        }
+    }
+    Ok(lints)
+}
+
+/// Applies fixes one at a time, re-linting after each, until none is left. A fix that
+/// would leave the file unparsable is skipped. Returns the number of fixes written.
+pub fn fix_skill_file(path: &Path, config: &LintConfig) -> Result<usize> {
+    let mut applied = 0;
+    let mut failed = Vec::new();
+
+    while applied < MAX_FIXES {
+        let content = std::fs::read_to_string(path)?;
+        let Some(fix) = lint_skill_file(path, config)?
+            .into_iter()
+            .filter_map(|lint| lint.fix)
+            .find(|fix| !failed.contains(fix))
+        else {
+            break;
+        };
+        match apply_fix(&content, &fix) {
+            Some(fixed) if SourceMap::new(&fixed).syntax_diagnostics().is_empty() => {
+                std::fs::write(path, fixed)?;
+                if parse_skill_file(path).is_ok() {
+                    applied += 1;
+                    continue;
+                }
+                std::fs::write(path, &content)?;
+            }
+            _ => {}
+        }
+        failed.push(fix);
+    }
+    Ok(applied)
+}
+
+/// Whether `code` is allowed for the whole file (a Markdown comment or a frontmatter
+/// comment) or, for step lints, by a comment in that step's block.
+fn is_suppressed(source: &SourceMap, target: &Target, code: &str) -> bool {
+    let allows = |line: &str| {
+        line.find(ALLOW_MARKER).is_some_and(|at| {
+            line[at + ALLOW_MARKER.len()..]
+                .trim_end()
+                .trim_end_matches("-->")
+                .split(',')
+                .any(|rule| rule.trim() == code)
+        })
+    };
+
+    let in_blocks = source
+        .blocks
+        .iter()
+        .flat_map(|block| block.start..block.end)
+        .collect::<HashSet<_>>();
+    let file_wide = (0..source.lines.len())
+        .filter(|i| !in_blocks.contains(i))
+        .any(|i| allows(source.line(i)));
+    if file_wide {
+        return true;
+    }
+
+    match target {
+        Target::Step { id, .. } => source
+            .block(id)
+            .is_some_and(|block| (block.start..block.end).any(|i| allows(source.line(i)))),
+        _ => false,
+    }
+}
+
+fn missing_tags(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    if !skill.metadata.tags.is_empty() {
+        return vec![];
+    }
+    vec![(
+        field("tags"),
+        format!("Skill '{}' has no tags", skill.metadata.name),
+        None,
+    )]
+}
+
+fn missing_examples(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    if !skill.metadata.examples.is_empty() {
+        return vec![];
+    }
+    vec![(
+        Target::Skill,
+        format!(
+            "Skill '{}' has no examples; selection relies on its description alone",
+            skill.metadata.name
+        ),
+        None,
+    )]
+}
+
+fn unused_input_var(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    let mut found = Vec::new();
+    for current in &skill.steps {
+        if !matches!(current.step_type, StepType::Llm) {
+            continue;
+        }
+        let used = current
+            .prompt
+            .as_deref()
+            .map(template_vars)
+            .unwrap_or_default();
+        for var in current.input_vars.iter().filter(|var| !used.contains(var)) {
+            let target = step(&current.id, Some("input_vars"));
+            found.push((
+                target.clone(),
+                format!(
+                    "Step '{}' lists '{var}' in input_vars but its prompt does not use it",
+                    current.id
+                ),
+                Some(Fix::RemoveListItem {
+                    target,
+                    item: var.clone(),
+                }),
+            ));
+        }
+    }
+    found
+}
+
+/// Called skills run with the intersection of permissions, so any runner, path or
+/// network access may be needed by a callee this file cannot see.
+fn calls_skills(skill: &Skill) -> bool {
+    skill
+        .steps
+        .iter()
+        .any(|step| matches!(step.step_type, StepType::Skill))
+}
+
+fn unused_allowed_path(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    // Path inputs are checked against allowed_paths when the skill runs.
+    let path_inputs = skill
+        .metadata
+        .inputs
+        .iter()
+        .any(|input| input.input_type == InputType::Path);
+    if calls_skills(skill) || path_inputs {
+        return vec![];
+    }
+    let commands = skill
+        .steps
+        .iter()
+        .filter_map(|step| step.cmd.as_deref())
+        .collect::<Vec<_>>();
+    skill
+        .metadata
+        .permissions
+        .allowed_paths
+        .iter()
+        .filter(|path| {
+            let path = path.trim_end_matches('/');
+            !commands.iter().any(|cmd| cmd.contains(path))
+        })
+        .map(|path| {
+            let target = field("permissions.allowed_paths");
+            (
+                target.clone(),
+                format!("allowed_paths lists '{path}' but no command step mentions it"),
+                Some(Fix::RemoveListItem {
+                    target,
+                    item: path.clone(),
+                }),
+            )
+        })
+        .collect()
+}
+
+fn unused_network_access(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    let needs_network = calls_skills(skill)
+        || skill
+            .steps
+            .iter()
+            .any(|step| matches!(step.step_type, StepType::Llm));
+    if !skill.metadata.permissions.network_access || needs_network {
+        return vec![];
+    }
+    let target = field("permissions.network_access");
+    vec![(
+        target.clone(),
+        "network_access is true but no step calls a model".to_string(),
+        Some(Fix::SetFalse {
+            target,
+            key: "network_access".to_string(),
+        }),
+    )]
+}
+
+fn broad_runner(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    if calls_skills(skill) {
+        return vec![];
+    }
+    let used = skill
+        .steps
+        .iter()
+        .filter_map(|step| step.runner.as_deref())
+        .collect::<HashSet<_>>();
+    skill
+        .metadata
+        .permissions
+        .allowed_runners
+        .iter()
+        .filter(|runner| !used.contains(runner.as_str()))
+        .map(|runner| {
+            let target = field("permissions.allowed_runners");
+            (
+                target.clone(),
+                format!("allowed_runners lists '{runner}' but no command step uses it"),
+                Some(Fix::RemoveListItem {
+                    target,
+                    item: runner.clone(),
+                }),
+            )
+        })
+        .collect()
+}
+
+fn step_without_heading(_: &Skill, source: &SourceMap) -> Vec<Finding> {
+    source
+        .blocks
+        .iter()
+        .filter(|block| block.heading.is_none())
+        .filter_map(|block| block.id.as_ref())
+        .map(|id| {
+            (
+                step(id, None),
+                format!("Step '{id}' has no heading describing it"),
+                Some(Fix::InsertHeading { id: id.clone() }),
+            )
+        })
+        .collect()
+}
+
+fn missing_output_step(skill: &Skill, _: &SourceMap) -> Vec<Finding> {
+    if skill.steps.is_empty()
+        || skill
+            .steps
+            .iter()
+            .any(|step| matches!(step.step_type, StepType::Output))
+    {
+        return vec![];
+    }
+    let fix = skill
+        .steps
+        .last()
+        .and_then(|last| last.output_var.clone())
+        .filter(|_| !skill.steps.iter().any(|step| step.id == "respond"))
+        .map(|var| Fix::AppendOutputStep {
+            var,
+            format: skill.metadata.response_format.format_type.clone(),
+        });
+    vec![(
+        field("response_format"),
+        format!(
+            "response_format is '{}' but no output step formats the result",
+            skill.metadata.response_format.format_type
+        ),
+        fix,
+    )]
+}
+
+/// `content` with `fix` applied, or `None` when its target cannot be found.
+pub fn apply_fix(content: &str, fix: &Fix) -> Option<String> {
+    let source = SourceMap::new(content);
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<_>>();
+
+    match fix {
+        Fix::SetFalse { target, key } => {
+            let (start, _) = region(&source, target)?;
+            let re = Regex::new(&format!(r"(\b{}\s*:\s*)true\b", regex::escape(key))).ok()?;
+            if !re.is_match(&lines[start]) {
+                return None;
+            }
+            lines[start] = re.replace(&lines[start], "${1}false").into_owned();
+        }
+        Fix::RemoveListItem { target, item } => {
+            let (start, end) = region(&source, target)?;
+            let key = match target {
+                Target::Field { path } => path.rsplit('.').next().unwrap_or(path),
+                Target::Step { field, .. } => field.as_deref()?,
+                Target::Skill => return None,
+            };
+            remove_list_item(&mut lines, start, end, key, item)?;
+        }
+        Fix::InsertHeading { id } => {
+            let fence = source.block(id)?.fence_line;
+            lines.splice(fence..fence, [format!("### Step: {id}"), String::new()]);
+        }
+        Fix::AppendOutputStep { var, format } => {
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+            lines.extend([
+                String::new(),
+                "### Step: respond".to_string(),
+                String::new(),
+                "```genai-step".to_string(),
+                "id: respond".to_string(),
+                "type: output".to_string(),
+                format!("format: {format}"),
+                format!("template: \"{{{{{var}}}}}\""),
+                "```".to_string(),
+            ]);
+        }
+    }
+
+    let mut fixed = lines.join("\n");
+    if content.ends_with('\n') {
+        fixed.push('\n');
+    }
+    Some(fixed)
+}
+
+fn region(source: &SourceMap, target: &Target) -> Option<(usize, usize)> {
+    match target {
+        Target::Field { path } => {
+            let (open, close) = source.frontmatter?;
+            let keys = path.split('.').collect::<Vec<_>>();
+            source.key_region(&keys, open + 1, close)
+        }
+        Target::Step { id, field } => {
+            let block = source.block(id)?;
+            source.key_region(&[field.as_deref()?], block.start, block.end)
+        }
+        Target::Skill => None,
+    }
+}
+
+/// Removes `item` from a flow list (`key: [a, b]`, also inside a flow mapping) or a
+/// block list (`- a` lines) under `key`. An emptied block list becomes `key: []`.
+fn remove_list_item(
+    lines: &mut Vec<String>,
+    start: usize,
+    end: usize,
+    key: &str,
+    item: &str,
+) -> Option<()> {
+    let unquote = |text: &str| text.trim().trim_matches(['"', '\'']).to_string();
+
+    let flow = Regex::new(&format!(r"(\b{}\s*:\s*\[)([^\]]*)\]", regex::escape(key))).ok()?;
+    if let Some(caps) = flow.captures(&lines[start]) {
+        let items = caps[2]
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .collect::<Vec<_>>();
+        let kept = items
+            .iter()
+            .filter(|entry| unquote(entry) != item)
+            .map(|entry| entry.trim())
+            .collect::<Vec<_>>();
+        if kept.len() == items.len() {
+            return None;
+        }
+        let replacement = format!("{}{}]", &caps[1], kept.join(", "));
+        lines[start] = flow
+            .replace(&lines[start], regex::NoExpand(&replacement))
+            .into_owned();
+        return Some(());
+    }
+
+    let entries = (start + 1..end)
+        .filter(|&i| lines[i].trim_start().starts_with("- "))
+        .collect::<Vec<_>>();
+    let remove = *entries
+        .iter()
+        .find(|&&i| unquote(&lines[i].trim_start()[2..]) == item)?;
+    lines.remove(remove);
+    if entries.len() == 1 {
+        let indent = lines[start].len() - lines[start].trim_start().len();
+        lines[start] = format!("{}{key}: []", " ".repeat(indent));
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fix_skill_file, lint_skill_file, LintConfig, LintLevel};
+    use crate::util::testing::TempRoot;
+
+    const SKILL: &str = "---\nname: notes\ndescription: d\nversion: 1.0.0\ncategory: docs\ntags: []\n\
+entrypoint: workflow\nworkflow_version: 1\n\
+capabilities: { requires_repo: false, supports_interactive: false }\n\
+permissions:\n  run_commands: true\n  allowed_runners: [bash, python]\n  allowed_paths:\n    - scripts/\n  network_access: true\n  write_access: false\n\
+response_format: { type: markdown }\n---\n\
+<!-- genai-lint: allow missing-examples -->\n\n\
+```genai-step\nid: log\ntype: command\nrunner: bash\ncmd: git log\noutput_var: log\n```\n\n\
+### Step: summarize\n\n\
+```genai-step\n# genai-lint: allow unused-input-var\nid: summarize\ntype: llm\nmodel: m\ninput_vars: [log]\n\
+prompt: Summarize the changes\noutput_var: notes\n```\n";
+
+    #[test]
+    fn reports_suppresses_and_fixes() {
+        let dir = TempRoot::new("lint");
+        let path = dir.join("SKILL.md");
+        std::fs::write(&path, SKILL).unwrap();
+
+        let mut config = LintConfig::default();
+        config
+            .rules
+            .insert("missing-tags".to_string(), LintLevel::Error);
+        let codes = |config: &LintConfig| {
+            lint_skill_file(&path, config)
+                .unwrap()
+                .into_iter()
+                .map(|lint| lint.diagnostic.code)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            codes(&config),
+            vec![
+                "missing-tags",
+                "unused-allowed-path",
+                "broad-runner",
+                "step-without-heading",
+                "missing-output-step",
+            ]
+        );
+
+        assert_eq!(fix_skill_file(&path, &config).unwrap(), 4);
+        assert_eq!(codes(&config), vec!["missing-tags"]);
+        let fixed = std::fs::read_to_string(&path).unwrap();
+        assert!(fixed.contains("allowed_runners: [bash]\n  allowed_paths: []\n"));
+        assert!(fixed.contains("### Step: log\n\n```genai-step\nid: log"));
+        assert!(fixed.ends_with("format: markdown\ntemplate: \"{{notes}}\"\n```\n"));
+
+        config
+            .rules
+            .insert("missing-tags".to_string(), LintLevel::Allow);
+        assert!(codes(&config).is_empty());
+
+        // A callee may need the runners and paths this file does not use itself.
+        let calling = SKILL.replace(
+            "```genai-step\nid: log\n",
+            "```genai-step\nid: helper\ntype: skill\nskill: helper\noutput_var: help\n```\n\n\
+```genai-step\nid: log\n",
+        );
+        std::fs::write(&path, calling).unwrap();
+        let codes = codes(&config);
+        assert!(!codes.contains(&"broad-runner"), "{codes:?}");
+        assert!(!codes.contains(&"unused-allowed-path"), "{codes:?}");
+    }
+}
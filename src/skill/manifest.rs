@@ -0,0 +1,38 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE: &str = "genai.yaml";
+
+/// Project-level `genai.yaml`, found in the current directory or an ancestor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectManifest {
+    /// Version requirement per skill name, e.g. `auto-commit-msg: ^1.2`.
+    #[serde(default)]
+    pub skills: BTreeMap<String, String>,
+    /// File-based registry used by `genai install <name>`, relative to the manifest.
+    pub registry: Option<String>,
+}
+
+impl ProjectManifest {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// The nearest manifest at or above `start`, with its path.
+    pub fn discover(start: &Path) -> Result<Option<(PathBuf, Self)>> {
+        match start
+            .ancestors()
+            .map(|dir| dir.join(MANIFEST_FILE))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Ok(Some((path.clone(), Self::from_path(&path)?))),
+            None => Ok(None),
+        }
+    }
+}
@@ -1,5 +1,17 @@
+pub mod catalog;
+pub mod dataflow;
+pub mod diagnostics;
+pub mod embedding;
+pub mod eval;
+pub mod format;
+pub mod inputs;
+pub mod lexical;
+pub mod lint;
+pub mod manifest;
 pub mod model;
 pub mod parser;
+pub mod planner;
+pub mod roots;
 pub mod scanner;
 pub mod selector;
 pub mod validator;
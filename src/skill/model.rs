@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,6 +8,25 @@ pub struct Skill {
     pub markdown_body: String,
     pub steps: Vec<WorkflowStep>,
     pub path: String,
+    /// Directory-derived namespace, e.g. `git` for `skills/git/auto-commit-msg/SKILL.md`.
+    #[serde(default)]
+    pub namespace: String,
+}
+
+impl Skill {
+    /// `namespace/name`, or just the name for skills at the top of a root.
+    pub fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.metadata.name.clone()
+        } else {
+            format!("{}/{}", self.namespace, self.metadata.name)
+        }
+    }
+
+    /// Whether `reference` names this skill, either fully qualified or by bare name.
+    pub fn matches_name(&self, reference: &str) -> bool {
+        self.metadata.name == reference || self.qualified_name() == reference
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +43,81 @@ pub struct SkillMetadata {
     pub capabilities: Capabilities,
     pub permissions: Permissions,
     pub response_format: ResponseFormat,
+
+    /// Parameters the skill accepts, exposed to steps as `{{name}}`.
+    #[serde(default)]
+    pub inputs: Vec<InputSpec>,
+
+    /// Sample requests this skill handles; they help offline selection.
+    #[serde(default)]
+    pub examples: Vec<String>,
+}
+
+impl SkillMetadata {
+    pub fn semver(&self) -> Result<semver::Version, semver::Error> {
+        semver::Version::parse(&self.version)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputSpec {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub input_type: InputType,
+    pub default: Option<InputValue>,
+    #[serde(default)]
+    pub required: bool,
+    pub description: Option<String>,
+    /// Allowed values for `type: enum`.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputType {
+    #[default]
+    String,
+    Int,
+    Bool,
+    Enum,
+    Path,
+    List,
+}
+
+impl InputType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputType::String => "string",
+            InputType::Int => "int",
+            InputType::Bool => "bool",
+            InputType::Enum => "enum",
+            InputType::Path => "path",
+            InputType::List => "list",
+        }
+    }
+}
+
+/// A literal input value as written in YAML or JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InputValue {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    List(Vec<String>),
+}
+
+impl InputValue {
+    /// The string form that `--set` would have supplied; lists are comma-separated.
+    pub fn to_raw(&self) -> String {
+        match self {
+            InputValue::Bool(value) => value.to_string(),
+            InputValue::Int(value) => value.to_string(),
+            InputValue::String(value) => value.clone(),
+            InputValue::List(values) => values.join(","),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +135,25 @@ pub struct Permissions {
     pub write_access: bool,
 }
 
+impl Permissions {
+    /// What both sides allow; a called skill runs with this so it never gains anything.
+    pub fn intersect(&self, other: &Permissions) -> Permissions {
+        let common = |a: &[String], b: &[String]| {
+            a.iter()
+                .filter(|item| b.contains(item))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        Permissions {
+            run_commands: self.run_commands && other.run_commands,
+            allowed_runners: common(&self.allowed_runners, &other.allowed_runners),
+            allowed_paths: common(&self.allowed_paths, &other.allowed_paths),
+            network_access: self.network_access && other.network_access,
+            write_access: self.write_access && other.write_access,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseFormat {
     #[serde(rename = "type")]
@@ -46,15 +161,28 @@ pub struct ResponseFormat {
     pub style: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum StepType {
+    #[default]
     Command,
     Llm,
     Output,
+    Skill,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl StepType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepType::Command => "command",
+            StepType::Llm => "llm",
+            StepType::Output => "output",
+            StepType::Skill => "skill",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct WorkflowStep {
     pub id: String,
     #[serde(rename = "type")]
@@ -70,7 +198,75 @@ pub struct WorkflowStep {
     #[serde(default)]
     pub input_vars: Vec<String>,
     pub prompt: Option<String>,
+    pub sampling: Option<Sampling>,
+    pub thinking_budget: Option<i32>,
+    pub include_thoughts: Option<bool>,
+    pub routing: Option<RoutingPolicy>,
 
     pub format: Option<String>,
     pub template: Option<String>,
+
+    /// Skill called by a `type: skill` step, by qualified or bare name.
+    pub skill: Option<String>,
+    /// Callee input name to a template rendered in the caller's context.
+    #[serde(default)]
+    pub with: BTreeMap<String, String>,
+    /// Caller variable to the callee variable copied back after the call.
+    #[serde(default)]
+    pub outputs: BTreeMap<String, String>,
+
+    /// What the step does; taken from its Markdown heading and prose when not set here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Where the step's `genai-step` block is defined.
+    #[serde(skip)]
+    pub source: Option<StepSource>,
+}
+
+/// File and 1-based line of a step's opening fence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepSource {
+    pub path: String,
+    pub line: usize,
+}
+
+/// Best-of-N settings for an llm step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sampling {
+    pub n: u32,
+    #[serde(default)]
+    pub strategy: SamplingStrategy,
+    /// Prompt for `strategy: judge`; `{{candidates}}` holds the numbered candidates.
+    pub judge_prompt: Option<String>,
+    pub judge_model: Option<String>,
+    /// Regex a candidate must match to be eligible for `strategy: shortest`.
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SamplingStrategy {
+    #[default]
+    Majority,
+    Judge,
+    Shortest,
+    Pick,
+}
+
+/// Picks an llm step's model from the size of its input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingPolicy {
+    /// Variable to measure; the whole rendered prompt when absent.
+    pub measure: Option<String>,
+    /// Checked in order; inputs at or above every `below` are refused.
+    pub rules: Vec<RoutingRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    /// Exclusive upper bound in estimated tokens.
+    pub below: u64,
+    /// Empty means the provider default model.
+    #[serde(default)]
+    pub model: String,
 }
@@ -1,40 +1,520 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::{anyhow, Context, Result};
-use regex::Regex;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde_yaml::{Mapping, Value};
 
-use crate::skill::model::{SkillMetadata, WorkflowStep};
+use crate::skill::format::{
+    find_skill_file, read_document, render_document, SkillDocument, SkillFormat,
+};
+use crate::skill::model::{Skill, SkillMetadata, StepSource, WorkflowStep};
 
 pub fn parse_frontmatter(content: &str) -> Result<(SkillMetadata, String)> {
-    let mut parts = content.splitn(3, "---");
-    let prefix = parts.next().unwrap_or_default();
-    if !prefix.trim().is_empty() {
-        return Err(anyhow!("SKILL.md must start with YAML frontmatter"));
-    }
-
-    let yaml = parts
-        .next()
-        .ok_or_else(|| anyhow!("Missing YAML frontmatter"))?;
-    let body = parts
-        .next()
-        .ok_or_else(|| anyhow!("Missing markdown body after frontmatter"))?;
-
+    let (yaml, body) = split_frontmatter(content)?;
     let metadata: SkillMetadata =
         serde_yaml::from_str(yaml).context("Failed to parse frontmatter YAML")?;
     Ok((metadata, body.trim_start().to_string()))
 }
 
+/// YAML frontmatter delimiters: opened by `---`, closed by `---` or `...`.
+pub const YAML_DELIMITERS: (&str, &[&str]) = ("---", &["---", "..."]);
+/// TOML frontmatter delimiters.
+pub const TOML_DELIMITERS: (&str, &[&str]) = ("+++", &["+++"]);
+
+/// 0-based indexes of the opening and closing `---` lines of YAML frontmatter.
+pub fn frontmatter_bounds(content: &str) -> Option<(usize, usize)> {
+    delimited_bounds(content, YAML_DELIMITERS)
+}
+
+/// The opening delimiter must be the first non-blank line; the frontmatter ends at the
+/// next line that is exactly a closing delimiter, so `---` inside a value does not end it.
+pub(crate) fn delimited_bounds(
+    content: &str,
+    (open, close): (&str, &[&str]),
+) -> Option<(usize, usize)> {
+    let mut lines = content.lines().enumerate();
+    let (start, _) = lines
+        .by_ref()
+        .find(|(_, line)| !line.trim().is_empty())
+        .filter(|(_, line)| line.trim_start_matches('\u{feff}').trim_end() == open)?;
+    let (end, _) = lines.find(|(_, line)| close.contains(&line.trim_end()))?;
+    Some((start, end))
+}
+
+fn split_frontmatter(content: &str) -> Result<(&str, &str)> {
+    split_delimited(content, YAML_DELIMITERS, "YAML")
+}
+
+/// `(frontmatter, body)` of a Markdown skill file.
+pub(crate) fn split_delimited<'a>(
+    content: &'a str,
+    delimiters: (&str, &[&str]),
+    language: &str,
+) -> Result<(&'a str, &'a str)> {
+    let first = content.lines().find(|line| !line.trim().is_empty());
+    if first.map(|line| line.trim_start_matches('\u{feff}').trim_end()) != Some(delimiters.0) {
+        return Err(anyhow!("SKILL.md must start with {language} frontmatter"));
+    }
+    let (open, close) = delimited_bounds(content, delimiters).ok_or_else(|| {
+        anyhow!(
+            "{language} frontmatter is not closed with a '{}' line",
+            delimiters.0
+        )
+    })?;
+
+    let offsets = line_offsets(content);
+    let at = |line: usize| offsets.get(line).copied().unwrap_or(content.len());
+    Ok((&content[at(open + 1)..at(close)], &content[at(close + 1)..]))
+}
+
+/// Byte offset at which each line starts.
+fn line_offsets(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// A fenced `genai-step` code block found by the CommonMark parser.
+#[derive(Debug, Clone)]
+pub struct StepFence {
+    /// The block's content, without fences or list indentation.
+    pub yaml: String,
+    /// 0-based line of the opening fence.
+    pub fence_line: usize,
+    /// 0-based lines of the content: the first, and one past the last.
+    pub start: usize,
+    pub end: usize,
+    /// Nearest heading since the previous step block, if any.
+    pub heading: Option<String>,
+    /// Paragraphs between that heading (or the previous block) and this one.
+    pub prose: Vec<String>,
+    /// 0-based line ranges of the heading, the paragraphs and the whole fenced block.
+    pub section: Vec<(usize, usize)>,
+}
+
+impl StepFence {
+    /// The heading (minus a `Step:` prefix and unless it just repeats `id`) and prose.
+    pub fn description(&self, id: &str) -> Option<String> {
+        let heading = self
+            .heading
+            .as_deref()
+            .map(|heading| {
+                heading
+                    .strip_prefix("Step:")
+                    .unwrap_or(heading)
+                    .trim()
+                    .to_string()
+            })
+            .filter(|heading| !heading.is_empty() && heading != id);
+        let parts = heading
+            .into_iter()
+            .chain(self.prose.iter().cloned())
+            .collect::<Vec<_>>();
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+}
+
+/// Every `genai-step` block in a Markdown document. Fences follow CommonMark, so a
+/// block inside a longer fence (```` ```` ````) or an HTML comment is not a step.
+pub fn find_step_fences(markdown: &str) -> Vec<StepFence> {
+    let offsets = line_offsets(markdown);
+    let line_of = |byte: usize| offsets.partition_point(|&start| start <= byte) - 1;
+
+    let lines_of = |range: &std::ops::Range<usize>| {
+        (
+            line_of(range.start),
+            line_of(range.end.saturating_sub(1)) + 1,
+        )
+    };
+
+    let mut fences = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut prose = Vec::new();
+    let mut section = Vec::new();
+    let mut text: Option<String> = None;
+    let mut step: Option<StepFence> = None;
+    // Only top-level headings and paragraphs document a step, not list items or quotes.
+    let mut nesting = 0usize;
+
+    for (event, range) in Parser::new_ext(markdown, Options::empty()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::List(_) | Tag::Item | Tag::BlockQuote(_)) => nesting += 1,
+            Event::End(TagEnd::List(_) | TagEnd::Item | TagEnd::BlockQuote(_)) => {
+                nesting = nesting.saturating_sub(1)
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                if info.split_whitespace().next() == Some("genai-step") =>
+            {
+                let fence_line = line_of(range.start);
+                let last = line_of(range.end.saturating_sub(1));
+                // An unclosed fence runs to the end of the document.
+                let closed = last > fence_line && {
+                    let line = markdown[offsets[last]..].trim_start();
+                    line.starts_with("```") || line.starts_with("~~~")
+                };
+                step = Some(StepFence {
+                    yaml: String::new(),
+                    fence_line,
+                    start: fence_line + 1,
+                    end: if closed { last } else { last + 1 },
+                    heading: heading.take(),
+                    prose: std::mem::take(&mut prose),
+                    section: std::mem::take(&mut section),
+                });
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(mut fence) = step.take() {
+                    fence.section.push(lines_of(&range));
+                    fences.push(fence);
+                }
+            }
+            Event::Start(Tag::Heading { .. } | Tag::Paragraph) if nesting == 0 => {
+                text = Some(String::new());
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some(title) = text.take() {
+                    heading = Some(title.trim().to_string());
+                    prose.clear();
+                    section = vec![lines_of(&range)];
+                }
+            }
+            Event::End(TagEnd::Paragraph) => {
+                if let Some(paragraph) = text.take() {
+                    prose.push(paragraph.trim().to_string());
+                    section.push(lines_of(&range));
+                }
+            }
+            Event::Text(value) | Event::Code(value) => {
+                if let Some(fence) = step.as_mut() {
+                    fence.yaml.push_str(&value);
+                } else if let Some(text) = text.as_mut() {
+                    text.push_str(&value);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(text) = text.as_mut() {
+                    text.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    fences
+}
+
+/// The Markdown with every step's fenced block, heading and prose removed: the
+/// documentation that is not attached to a step. Runs of blank lines collapse to one.
+pub fn strip_step_sections(markdown: &str) -> String {
+    let owned = find_step_fences(markdown)
+        .into_iter()
+        .flat_map(|fence| fence.section)
+        .flat_map(|(start, end)| start..end)
+        .collect::<std::collections::HashSet<_>>();
+    let mut out = Vec::new();
+    for (i, line) in markdown.lines().enumerate() {
+        let blank = line.trim().is_empty();
+        if owned.contains(&i)
+            || (blank && out.last().is_none_or(|last: &&str| last.trim().is_empty()))
+        {
+            continue;
+        }
+        out.push(line);
+    }
+    out.join("\n").trim().to_string()
+}
+
 pub fn parse_genai_steps(markdown_body: &str) -> Result<Vec<WorkflowStep>> {
-    let re = Regex::new(r"(?s)```genai-step\s*(.*?)\s*```")?;
+    find_step_fences(markdown_body)
+        .into_iter()
+        .map(|fence| {
+            let mut step: WorkflowStep = serde_yaml::from_str(&fence.yaml).with_context(|| {
+                format!(
+                    "Failed parsing genai-step YAML at line {}",
+                    fence.fence_line + 1
+                )
+            })?;
+            if step.description.is_none() {
+                step.description = fence.description(&step.id);
+            }
+            step.source = Some(StepSource {
+                path: String::new(),
+                line: fence.fence_line + 1,
+            });
+            Ok(step)
+        })
+        .collect()
+}
+
+/// Parses a skill file in any `SkillFormat` and applies its `extends:` base and
+/// `include:` fragments.
+///
+/// Both are paths relative to the file's directory (a directory means its skill file).
+/// The base's frontmatter is deep-merged under the child's, except `name`. Steps are
+/// the base's, then each fragment's, then the file's own; a step whose id already
+/// exists replaces it in place, any other step is appended.
+pub fn parse_skill_file(path: &Path) -> Result<(SkillMetadata, String, Vec<WorkflowStep>)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_skill_content(path, &content)
+}
+
+/// [`parse_skill_file`] with the file's content already in hand; `path` still locates
+/// its base skill and includes.
+pub fn parse_skill_content(
+    path: &Path,
+    content: &str,
+) -> Result<(SkillMetadata, String, Vec<WorkflowStep>)> {
+    let (frontmatter, body, steps) = resolve_content(path, content, &mut Vec::new())?;
+    let metadata: SkillMetadata = serde_yaml::from_value(Value::Mapping(frontmatter))
+        .context("Failed to parse frontmatter YAML")?;
+    Ok((metadata, body, steps))
+}
+
+/// Whether a skill file's frontmatter uses `extends` or `include`.
+pub fn uses_inheritance(path: &Path, content: &str) -> bool {
+    read_document(content, SkillFormat::detect(path, content)).is_ok_and(|document| {
+        document.frontmatter.contains_key("extends") || document.frontmatter.contains_key("include")
+    })
+}
+
+fn resolve_file(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<(Mapping, String, Vec<WorkflowStep>)> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    resolve_content(path, &content, chain)
+}
+
+fn resolve_content(
+    path: &Path,
+    content: &str,
+    chain: &mut Vec<PathBuf>,
+) -> Result<(Mapping, String, Vec<WorkflowStep>)> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if chain.contains(&canonical) {
+        return Err(anyhow!("extends cycle through {}", path.display()));
+    }
+    chain.push(canonical);
+
+    let document = read_document(content, SkillFormat::detect(path, content))?;
+    let mut frontmatter = document.frontmatter;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let extends = frontmatter.remove("extends");
+    let includes = frontmatter.remove("include");
+
     let mut steps = Vec::new();
+    if let Some(extends) = extends {
+        let base = extends
+            .as_str()
+            .ok_or_else(|| anyhow!("extends must be a path"))?;
+        let (mut base_frontmatter, _, base_steps) = resolve_file(&skill_file(dir, base), chain)
+            .with_context(|| format!("Failed to load base skill {base}"))?;
+        base_frontmatter.remove("name");
+        merge_mapping(&mut base_frontmatter, frontmatter);
+        frontmatter = base_frontmatter;
+        steps = base_steps;
+    }
+
+    if let Some(includes) = includes {
+        let includes: Vec<String> =
+            serde_yaml::from_value(includes).context("include must be a list of paths")?;
+        for include in includes {
+            let fragment = std::fs::read_to_string(dir.join(&include))
+                .with_context(|| format!("Failed to read include {include}"))?;
+            let fragment_steps = parse_genai_steps(&fragment)
+                .with_context(|| format!("Failed to parse include {include}"))?;
+            merge_steps(&mut steps, located(fragment_steps, &dir.join(&include), 0));
+        }
+    }
+
+    merge_steps(&mut steps, located(document.steps, path, 0));
+    chain.pop();
+    Ok((frontmatter, document.body.trim_start().to_string(), steps))
+}
+
+/// Points each step's source at `path`, shifting lines by `offset`.
+fn located(mut steps: Vec<WorkflowStep>, path: &Path, offset: usize) -> Vec<WorkflowStep> {
+    for step in &mut steps {
+        if let Some(source) = step.source.as_mut() {
+            source.path = path.to_string_lossy().to_string();
+            source.line += offset;
+        }
+    }
+    steps
+}
+
+fn skill_file(dir: &Path, reference: &str) -> PathBuf {
+    let path = dir.join(reference);
+    if path.is_dir() {
+        find_skill_file(&path).unwrap_or_else(|| path.join("SKILL.md"))
+    } else {
+        path
+    }
+}
 
-    for cap in re.captures_iter(markdown_body) {
-        let yaml = cap
-            .get(1)
-            .ok_or_else(|| anyhow!("Invalid genai-step capture"))?
-            .as_str();
-        let step: WorkflowStep =
-            serde_yaml::from_str(yaml).context("Failed parsing genai-step YAML")?;
-        steps.push(step);
+/// Nested mappings merge key by key; any other value in `overlay` replaces the base.
+fn merge_mapping(base: &mut Mapping, overlay: Mapping) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Mapping(existing)), Value::Mapping(value)) => {
+                merge_mapping(existing, value)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
     }
+}
 
-    Ok(steps)
+fn merge_steps(steps: &mut Vec<WorkflowStep>, overlay: Vec<WorkflowStep>) {
+    for step in overlay {
+        match steps.iter_mut().find(|existing| existing.id == step.id) {
+            Some(existing) => *existing = step,
+            None => steps.push(step),
+        }
+    }
+}
+
+/// Renders a skill as a single SKILL.md with inheritance and includes flattened. Each
+/// step gets a `### Step: <id>` heading followed by its description.
+pub fn render_resolved(skill: &Skill) -> Result<String> {
+    render_skill(skill, SkillFormat::Markdown)
+}
+
+/// Renders a resolved skill in `format`. The body keeps its prose; its step sections
+/// are dropped because the steps are written from `skill.steps`.
+pub fn render_skill(skill: &Skill, format: SkillFormat) -> Result<String> {
+    let frontmatter = match serde_yaml::to_value(&skill.metadata)? {
+        Value::Mapping(mapping) => mapping,
+        _ => return Err(anyhow!("Skill metadata is not a mapping")),
+    };
+    render_document(
+        &SkillDocument {
+            frontmatter,
+            body: strip_step_sections(&skill.markdown_body),
+            steps: skill.steps.clone(),
+        },
+        format,
+    )
+}
+
+/// Drops nulls (and with `drop_empty`, empty collections) so unset optional fields are
+/// not printed.
+pub(crate) fn prune(value: Value, drop_empty: bool) -> Value {
+    match value {
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .into_iter()
+                .map(|(key, value)| (key, prune(value, drop_empty)))
+                .filter(|(_, value)| match value {
+                    Value::Null => false,
+                    Value::Mapping(m) => !drop_empty || !m.is_empty(),
+                    Value::Sequence(s) => !drop_empty || !s.is_empty(),
+                    _ => true,
+                })
+                .collect(),
+        ),
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .into_iter()
+                .map(|item| prune(item, drop_empty))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_frontmatter, parse_skill_file};
+    use crate::util::testing::TempRoot;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn merges_base_metadata_includes_and_steps_by_id() {
+        let root = TempRoot::new("parser");
+        write(
+            &root,
+            ".base/git/SKILL.md",
+            "---\nname: git-base\ndescription: base\nversion: 1.0.0\ncategory: git\ntags: [git]\n\
+entrypoint: workflow\nworkflow_version: 1\n\
+capabilities: { requires_repo: true, supports_interactive: false }\n\
+permissions: { run_commands: true, allowed_runners: [bash], allowed_paths: [], network_access: false, write_access: false }\n\
+response_format: { type: text }\n---\n\
+```genai-step\nid: diff\ntype: command\nrunner: bash\ncmd: git diff --staged\noutput_var: diff\n```\n\
+```genai-step\nid: respond\ntype: output\ntemplate: \"{{diff}}\"\n```\n",
+        );
+        write(
+            &root,
+            "shared/fallback.md",
+            "```genai-step\nid: fallback\ntype: command\nrunner: bash\ncmd: git diff\noutput_var: diff\n```\n",
+        );
+        write(
+            &root,
+            "commit/SKILL.md",
+            "---\nname: commit\ndescription: child\nextends: ../.base/git\ninclude: [../shared/fallback.md]\n\
+permissions: { network_access: true }\n---\n\
+```genai-step\nid: respond\ntype: output\ntemplate: \"commit: {{diff}}\"\n```\n",
+        );
+
+        let (metadata, _, steps) =
+            parse_skill_file(&root.join("commit/SKILL.md")).expect("skill should resolve");
+        assert_eq!(metadata.name, "commit");
+        assert_eq!(metadata.category, "git");
+        assert!(metadata.permissions.run_commands);
+        assert!(metadata.permissions.network_access);
+
+        let ids = steps.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["diff", "respond", "fallback"]);
+        assert_eq!(steps[1].template.as_deref(), Some("commit: {{diff}}"));
+    }
+
+    #[test]
+    fn follows_commonmark_fences_and_keeps_step_docs() {
+        let root = TempRoot::new("parser-md");
+        write(
+            &root,
+            "notes/SKILL.md",
+            "---\nname: notes\ndescription: |\n  Splits on\n  ---\n  lines\nversion: 1.0.0\ncategory: docs\ntags: []\n\
+entrypoint: workflow\nworkflow_version: 1\n\
+capabilities: { requires_repo: false, supports_interactive: false }\n\
+permissions: { run_commands: true, allowed_runners: [bash], allowed_paths: [], network_access: false, write_access: false }\n\
+response_format: { type: text }\n---\n\
+# Workflow\n\n\
+An example that is not a step:\n\n\
+````md\n```genai-step\nid: example\ntype: output\n```\n````\n\n\
+### Step: Collect the log\n\n\
+Reads recent history.\nOne line per commit.\n\n\
+~~~~genai-step\nid: log\ntype: command\nrunner: bash\ncmd: \"printf '```'\"\noutput_var: log\n~~~~\n\n\
+```genai-step\nid: respond\ntype: output\ntemplate: \"{{log}}\"\n```\n",
+        );
+
+        let content = std::fs::read_to_string(root.join("notes/SKILL.md")).unwrap();
+        let (metadata, _) = parse_frontmatter(&content).unwrap();
+        assert_eq!(metadata.description, "Splits on\n---\nlines\n");
+
+        let (_, _, steps) = parse_skill_file(&root.join("notes/SKILL.md")).unwrap();
+        let ids = steps.iter().map(|s| s.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["log", "respond"]);
+        assert_eq!(steps[0].cmd.as_deref(), Some("printf '```'"));
+        assert_eq!(
+            steps[0].description.as_deref(),
+            Some("Collect the log\n\nReads recent history. One line per commit.")
+        );
+        assert_eq!(steps[1].description, None);
+        let source = steps[0].source.as_ref().unwrap();
+        assert!(source.path.ends_with("notes/SKILL.md"));
+        assert_eq!(source.line, 32);
+    }
 }
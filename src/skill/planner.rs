@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::llm::client::LlmClient;
+use crate::llm::prompt::build_planner_prompt;
+use crate::skill::model::Skill;
+use crate::skill::selector::SelectorConfig;
+use crate::util::json::extract_json_object;
+
+/// Longest plan accepted from the model.
+pub const MAX_PLAN_STEPS: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct PlannerResponse {
+    steps: Vec<PlannerStep>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlannerStep {
+    skill: String,
+    #[serde(default)]
+    prompt: Option<String>,
+    #[serde(default)]
+    inputs: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    reason: String,
+}
+
+/// One skill invocation in a plan. `prompt` and `inputs` are templates that may use
+/// `{{user_input}}`, `{{previous_output}}` and `{{stepN_output}}`.
+#[derive(Debug, Clone)]
+pub struct PlannedCall<'a> {
+    pub skill: &'a Skill,
+    pub prompt: Option<String>,
+    pub inputs: BTreeMap<String, String>,
+    pub reason: String,
+}
+
+/// Asks the model for an ordered list of skill invocations that together handle
+/// `user_input`. Only skills in `skills` can be planned.
+pub fn plan_skills<'a>(
+    user_input: &str,
+    skills: &'a [Skill],
+    llm: &dyn LlmClient,
+    config: &SelectorConfig,
+) -> Result<Vec<PlannedCall<'a>>> {
+    if skills.is_empty() {
+        return Err(anyhow!("No skills found"));
+    }
+
+    let prompt = build_planner_prompt(user_input, skills, MAX_PLAN_STEPS);
+    let reply = llm
+        .generate(&config.model, &prompt)
+        .context("Planner request failed")?;
+    let json = extract_json_object(&reply)
+        .ok_or_else(|| anyhow!("Planner reply contains no JSON plan: {reply}"))?;
+    let parsed: PlannerResponse =
+        serde_json::from_str(json).context("Planner reply is not a valid plan")?;
+
+    if parsed.steps.is_empty() {
+        return Err(anyhow!("Planner returned an empty plan"));
+    }
+    if parsed.steps.len() > MAX_PLAN_STEPS {
+        return Err(anyhow!(
+            "Planner returned {} steps; at most {MAX_PLAN_STEPS} are allowed",
+            parsed.steps.len()
+        ));
+    }
+
+    parsed
+        .steps
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let skill = skills
+                .iter()
+                .find(|skill| skill.matches_name(&step.skill))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Plan step {} uses unknown skill '{}'",
+                        i + 1,
+                        step.skill
+                    )
+                })?;
+
+            let inputs = step
+                .inputs
+                .into_iter()
+                .map(|(name, value)| {
+                    if !skill.metadata.inputs.iter().any(|spec| spec.name == name) {
+                        return Err(anyhow!(
+                            "Plan step {} sets undeclared input '{name}' on {}",
+                            i + 1,
+                            skill.qualified_name()
+                        ));
+                    }
+                    let value = match value {
+                        serde_json::Value::String(text) => text,
+                        other => other.to_string(),
+                    };
+                    Ok((name, value))
+                })
+                .collect::<Result<_>>()?;
+
+            Ok(PlannedCall {
+                skill,
+                prompt: step.prompt.filter(|p| !p.trim().is_empty()),
+                inputs,
+                reason: step.reason,
+            })
+        })
+        .collect()
+}
+
+/// Numbered plan for confirmation.
+pub fn render_plan(plan: &[PlannedCall]) -> String {
+    plan.iter()
+        .enumerate()
+        .map(|(i, call)| {
+            let mut line = format!("{}. {}", i + 1, call.skill.qualified_name());
+            if !call.reason.is_empty() {
+                line.push_str(&format!(" - {}", call.reason));
+            }
+            if let Some(prompt) = &call.prompt {
+                line.push_str(&format!("\n   prompt: {prompt}"));
+            }
+            for (name, value) in &call.inputs {
+                line.push_str(&format!("\n   {name} = {value}"));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::plan_skills;
+    use crate::llm::client::LlmClient;
+    use crate::skill::model::Skill;
+    use crate::skill::selector::SelectorConfig;
+    use crate::util::testing::test_skill;
+
+    struct Scripted(&'static str);
+
+    impl LlmClient for Scripted {
+        fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn skill(name: &str) -> Skill {
+        let mut skill = test_skill(name);
+        skill.metadata.inputs =
+            vec![serde_yaml::from_str("{ name: limit, type: int, default: 10 }").unwrap()];
+        skill
+    }
+
+    #[test]
+    fn plans_only_discovered_skills_and_declared_inputs() {
+        let skills = vec![skill("commit"), skill("pr-description")];
+        let config = SelectorConfig::default();
+
+        let llm = Scripted(
+            "Plan:\n```json\n{\"steps\":[{\"skill\":\"commit\",\"reason\":\"first\"},\
+{\"skill\":\"pr-description\",\"prompt\":\"Describe: {{previous_output}}\",\"inputs\":{\"limit\":3}}]}\n```",
+        );
+        let plan = plan_skills("commit and open a PR", &skills, &llm, &config).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[0].skill.metadata.name, "commit");
+        assert_eq!(plan[1].inputs["limit"], "3");
+        assert_eq!(
+            plan[1].prompt.as_deref(),
+            Some("Describe: {{previous_output}}")
+        );
+
+        let unknown = Scripted(r#"{"steps":[{"skill":"deploy"}]}"#);
+        let err = plan_skills("ship it", &skills, &unknown, &config).unwrap_err();
+        assert!(err.to_string().contains("unknown skill 'deploy'"), "{err}");
+
+        let undeclared = Scripted(r#"{"steps":[{"skill":"commit","inputs":{"force":true}}]}"#);
+        assert!(plan_skills("commit", &skills, &undeclared, &config).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootKind {
+    Cli,
+    Env,
+    Project,
+    User,
+    Team,
+    System,
+}
+
+impl RootKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RootKind::Cli => "cli",
+            RootKind::Env => "env",
+            RootKind::Project => "project",
+            RootKind::User => "user",
+            RootKind::Team => "team",
+            RootKind::System => "system",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkillRoot {
+    pub kind: RootKind,
+    pub path: PathBuf,
+}
+
+const SYSTEM_SKILLS_DIR: &str = "/usr/share/genai/skills";
+
+/// Skill directories in precedence order, highest first:
+///
+/// 1. every `--skills-dir`
+/// 2. `GENAI_SKILLS_DIR` (a `:`-separated path list)
+/// 3. the nearest `.genai/skills` from the current directory upwards
+/// 4. `~/GenAI/skills`
+/// 5. `GENAI_TEAM_SKILLS_DIR` (a `:`-separated path list)
+/// 6. `/usr/share/genai/skills`
+///
+/// Repeats of an earlier root are skipped. A missing `--skills-dir` is an error and a
+/// missing `GENAI_SKILLS_DIR` entry a warning, since both were named explicitly; the
+/// other roots are skipped silently when absent.
+pub fn resolve_skill_roots(cli_dirs: &[String]) -> Result<Vec<SkillRoot>> {
+    let mut candidates = Vec::new();
+
+    candidates.extend(
+        cli_dirs
+            .iter()
+            .map(|dir| (RootKind::Cli, PathBuf::from(dir))),
+    );
+    if let Ok(dirs) = std::env::var("GENAI_SKILLS_DIR") {
+        candidates.extend(std::env::split_paths(&dirs).map(|dir| (RootKind::Env, dir)));
+    }
+    if let Some(dir) = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| find_project_root(&cwd))
+    {
+        candidates.push((RootKind::Project, dir));
+    }
+    if let Ok(home) = std::env::var("HOME") {
+        candidates.push((
+            RootKind::User,
+            PathBuf::from(format!("{home}/GenAI/skills")),
+        ));
+    }
+    if let Ok(dirs) = std::env::var("GENAI_TEAM_SKILLS_DIR") {
+        candidates.extend(std::env::split_paths(&dirs).map(|dir| (RootKind::Team, dir)));
+    }
+    candidates.push((RootKind::System, PathBuf::from(SYSTEM_SKILLS_DIR)));
+
+    let mut roots: Vec<SkillRoot> = Vec::new();
+    for (kind, path) in candidates {
+        if !path.is_dir() {
+            match kind {
+                RootKind::Cli => {
+                    return Err(anyhow!("Skills directory not found: {}", path.display()))
+                }
+                RootKind::Env => warn!(
+                    "GENAI_SKILLS_DIR entry {} is not a directory, skipping it",
+                    path.display()
+                ),
+                _ => {}
+            }
+            continue;
+        }
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if roots.iter().any(|root| {
+            root.path
+                .canonicalize()
+                .unwrap_or_else(|_| root.path.clone())
+                == canonical
+        }) {
+            continue;
+        }
+        roots.push(SkillRoot { kind, path });
+    }
+    Ok(roots)
+}
+
+fn find_project_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(".genai").join("skills"))
+        .find(|dir| dir.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_skill_roots;
+
+    #[test]
+    fn missing_cli_dir_is_an_error() {
+        let missing = std::env::temp_dir().join(format!("genai-roots-{}", std::process::id()));
+        let err = resolve_skill_roots(&[missing.to_string_lossy().to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Skills directory not found"));
+    }
+}
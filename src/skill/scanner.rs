@@ -1,29 +1,334 @@
-use anyhow::Result;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::Serialize;
+use tracing::debug;
 use walkdir::WalkDir;
 
+use crate::package::signing::{enforce_trust, TrustConfig};
+use crate::skill::format::{find_skill_file, is_skill_file};
 use crate::skill::model::Skill;
-use crate::skill::parser::{parse_frontmatter, parse_genai_steps};
+use crate::skill::parser::parse_skill_file;
+
+pub const IGNORE_FILE: &str = ".genaiignore";
+
+/// Skills found under one root plus the entries that could not be loaded.
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    pub skills: Vec<Skill>,
+    pub problems: Vec<ScanProblem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProblem {
+    pub path: String,
+    pub message: String,
+}
+
+/// Finds every `SKILL.md`, `skill.yaml` or `skill.toml` below `skills_dir` at any depth.
+/// Parent directories become the skill's namespace, so `git/auto-commit-msg/SKILL.md` is
+/// addressable as `git/<name>`. A directory with several skill files loads the first of
+/// `SKILL_FILE_NAMES` and reports the rest.
+///
+/// Symlinks are followed (loops are reported, not fatal), hidden entries are skipped, and
+/// `.genaiignore` files exclude paths relative to the directory they live in. Skills
+/// failing the `trust` policy are reported as problems before anything can run them.
+pub fn scan_skills(skills_dir: &str, trust: &TrustConfig) -> Result<ScanReport> {
+    let root = Path::new(skills_dir);
+    if !root.is_dir() {
+        return Err(anyhow!("Skills directory not found: {skills_dir}"));
+    }
 
-pub fn scan_skills(skills_dir: &str) -> Result<Vec<Skill>> {
-    let mut skills = Vec::new();
+    let ignores = IgnoreRules::new(root);
+    let mut report = ScanReport::default();
 
-    for entry in WalkDir::new(skills_dir).min_depth(2).max_depth(2) {
-        let entry = entry?;
-        if !entry.file_type().is_file() || entry.file_name() != "SKILL.md" {
+    let walker = WalkDir::new(root)
+        .follow_links(true)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let hidden = entry.depth() > 0 && entry.file_name().to_string_lossy().starts_with('.');
+            !hidden && !ignores.is_ignored(entry.path())
+        });
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                let path = err
+                    .path()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|| skills_dir.to_string());
+                report.problems.push(ScanProblem {
+                    path,
+                    message: err.to_string(),
+                });
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() || !is_skill_file(entry.file_name()) || entry.depth() < 2 {
+            continue;
+        }
+        let preferred = entry.path().parent().and_then(find_skill_file);
+        if let Some(preferred) = preferred.filter(|preferred| preferred != entry.path()) {
+            report.problems.push(ScanProblem {
+                path: entry.path().to_string_lossy().to_string(),
+                message: format!(
+                    "Ignored: {} is loaded from this directory",
+                    preferred.display()
+                ),
+            });
             continue;
         }
 
-        let content = std::fs::read_to_string(entry.path())?;
-        let (metadata, markdown_body) = parse_frontmatter(&content)?;
-        let steps = parse_genai_steps(&markdown_body)?;
+        match load_skill(root, entry.path())
+            .and_then(|skill| enforce_trust(&skill, trust).map(|_| skill))
+        {
+            Ok(skill) => {
+                debug!(skill = %skill.qualified_name(), path = %skill.path, "Discovered skill");
+                report.skills.push(skill);
+            }
+            Err(err) => report.problems.push(ScanProblem {
+                path: entry.path().to_string_lossy().to_string(),
+                message: format!("{err:#}"),
+            }),
+        }
+    }
 
-        skills.push(Skill {
-            metadata,
-            markdown_body,
-            steps,
-            path: entry.path().to_string_lossy().to_string(),
-        });
+    Ok(report)
+}
+
+fn load_skill(root: &Path, path: &Path) -> Result<Skill> {
+    let (metadata, markdown_body, steps) = parse_skill_file(path)?;
+
+    Ok(Skill {
+        metadata,
+        markdown_body,
+        steps,
+        path: path.to_string_lossy().to_string(),
+        namespace: namespace_for(root, path),
+    })
+}
+
+/// `root/a/b/skill-dir/SKILL.md` has namespace `a/b`.
+fn namespace_for(root: &Path, skill_file: &Path) -> String {
+    skill_file
+        .parent()
+        .and_then(Path::parent)
+        .and_then(|dir| dir.strip_prefix(root).ok())
+        .map(|rel| {
+            rel.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_default()
+}
+
+/// Lazily loaded `.genaiignore` patterns for each directory under the root.
+struct IgnoreRules<'a> {
+    root: &'a Path,
+    cache: RefCell<HashMap<PathBuf, Vec<IgnorePattern>>>,
+}
+
+struct IgnorePattern {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl<'a> IgnoreRules<'a> {
+    fn new(root: &'a Path) -> Self {
+        Self {
+            root,
+            cache: RefCell::new(HashMap::new()),
+        }
     }
 
-    Ok(skills)
+    /// Later patterns win, and deeper ignore files win over shallower ones.
+    fn is_ignored(&self, path: &Path) -> bool {
+        let Ok(rel) = path.strip_prefix(self.root) else {
+            return false;
+        };
+        let is_dir = path.is_dir();
+
+        let mut ignored = false;
+        let mut dir = self.root.to_path_buf();
+        let components = rel.components().collect::<Vec<_>>();
+        for depth in 0..components.len() {
+            let rel_to_dir = components[depth..]
+                .iter()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let mut cache = self.cache.borrow_mut();
+            let patterns = cache
+                .entry(dir.clone())
+                .or_insert_with(|| load_ignore_file(&dir.join(IGNORE_FILE)));
+            for pattern in patterns.iter() {
+                if pattern.dir_only && !is_dir {
+                    continue;
+                }
+                if pattern.regex.is_match(&rel_to_dir) {
+                    ignored = !pattern.negated;
+                }
+            }
+
+            dir.push(components[depth]);
+        }
+        ignored
+    }
+}
+
+fn load_ignore_file(path: &Path) -> Vec<IgnorePattern> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content.lines().filter_map(parse_ignore_line).collect()
+}
+
+/// Gitignore-style line: `*`, `**`, `?`, leading `!` negates, trailing `/` matches
+/// directories only, and a pattern without `/` matches a name at any depth.
+fn parse_ignore_line(line: &str) -> Option<IgnorePattern> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    let anchored = line.contains('/');
+    let line = line.trim_start_matches('/');
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            other => regex.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    regex.push('$');
+
+    Regex::new(&regex).ok().map(|regex| IgnorePattern {
+        regex,
+        negated,
+        dir_only,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_ignore_line, scan_skills};
+    use crate::package::signing::TrustConfig;
+    use crate::util::testing::{skill_md, TempRoot};
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn discovers_nested_skills_with_namespaces_and_skips_broken_ones() {
+        let root = TempRoot::new("scan-nested");
+        write(
+            &root,
+            "git/auto-commit-msg/SKILL.md",
+            &skill_md("auto-commit-msg", "d", "1.0.0"),
+        );
+        write(
+            &root,
+            "git/pr/describe/SKILL.md",
+            &skill_md("describe", "d", "1.0.0"),
+        );
+        write(&root, "top/SKILL.md", &skill_md("top", "d", "1.0.0"));
+        write(&root, "broken/SKILL.md", "no frontmatter here");
+        write(&root, "drafts/wip/SKILL.md", &skill_md("wip", "d", "1.0.0"));
+        write(&root, ".genaiignore", "# work in progress\ndrafts/\n");
+
+        let report = scan_skills(&root.to_string_lossy(), &TrustConfig::default())
+            .expect("scan should succeed");
+        let mut names = report
+            .skills
+            .iter()
+            .map(|s| s.qualified_name())
+            .collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(names, vec!["git/auto-commit-msg", "git/pr/describe", "top"]);
+        assert_eq!(report.problems.len(), 1);
+        assert!(report.problems[0].path.ends_with("broken/SKILL.md"));
+    }
+
+    #[test]
+    fn loads_yaml_and_toml_skills_and_reports_shadowed_files() {
+        let root = TempRoot::new("scan-formats");
+        let yaml = skill_md("from-yaml", "d", "1.0.0")
+            .trim_start_matches("---\n")
+            .split("---\n")
+            .next()
+            .unwrap()
+            .to_string();
+        write(
+            &root,
+            "y/skill.yaml",
+            &format!(
+                "{yaml}steps:\n  - {{ id: a, type: llm, model: m, prompt: hi, output_var: out }}\n"
+            ),
+        );
+        write(
+            &root,
+            "t/SKILL.md",
+            "+++\nname = 'from-toml'\ndescription = 'd'\nversion = '1.0.0'\ncategory = 'test'\n\
+tags = []\nentrypoint = 'workflow'\nworkflow_version = 1\n\
+capabilities = { requires_repo = false, supports_interactive = false }\n\
+permissions = { run_commands = false, allowed_runners = [], allowed_paths = [], network_access = false, write_access = false }\n\
+response_format = { type = 'text' }\n+++\n# toml\n",
+        );
+        write(&root, "t/skill.toml", "name = 'shadowed'\n");
+
+        let report = scan_skills(&root.to_string_lossy(), &TrustConfig::default()).unwrap();
+        let names = report
+            .skills
+            .iter()
+            .map(|s| s.metadata.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["from-toml", "from-yaml"]);
+        assert_eq!(report.skills[1].steps[0].id, "a");
+        assert_eq!(report.problems.len(), 1);
+        assert!(report.problems[0].path.ends_with("t/skill.toml"));
+    }
+
+    #[test]
+    fn ignore_patterns_follow_gitignore_conventions() {
+        let pattern = parse_ignore_line("*.bak").unwrap();
+        assert!(pattern.regex.is_match("a/b/c.bak"));
+
+        let anchored = parse_ignore_line("/legacy/**").unwrap();
+        assert!(anchored.regex.is_match("legacy/x/SKILL.md"));
+        assert!(!anchored.regex.is_match("git/legacy/x"));
+
+        assert!(parse_ignore_line("!keep").unwrap().negated);
+        assert!(parse_ignore_line("# comment").is_none());
+    }
 }
@@ -1,69 +1,505 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
 use anyhow::{anyhow, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use crate::llm::client::LlmClient;
 use crate::llm::prompt::build_selector_prompt;
+use crate::skill::embedding::EmbeddingIndex;
+use crate::skill::lexical::LexicalIndex;
 use crate::skill::model::Skill;
+use crate::util::json::extract_json_object;
 
 #[derive(Debug, Deserialize)]
 struct SelectorResponse {
     skill: String,
+    /// A missing confidence counts as 0, so the pick needs confirmation.
+    #[serde(default)]
+    confidence: f64,
+    #[serde(default)]
+    reason: String,
+    #[serde(default)]
+    alternatives: Vec<Alternative>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Alternative {
+    skill: String,
+    #[serde(default)]
     confidence: f64,
+    #[serde(default)]
     reason: String,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectorMode {
+    /// Send the whole catalog to the model on every run.
+    #[default]
+    Llm,
+    /// Rank by cosine similarity against a cached embedding index.
+    Embedding,
+}
+
+/// `selector:` section of the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SelectorConfig {
+    pub mode: SelectorMode,
+    /// Selections below this confidence are not run without confirmation.
+    pub confidence_threshold: f64,
+    /// How many candidates to ask for and offer.
+    pub top_k: usize,
+    /// Model used for selection; empty means the provider default.
+    pub model: String,
+    /// Lowest BM25 score the offline fallback accepts.
+    pub min_score: f64,
+    /// Model used to embed skills and prompts in `embedding` mode.
+    pub embedding_model: String,
+    /// Lowest cosine similarity `embedding` mode runs without confirmation. Similarity
+    /// is not a confidence, so it has its own threshold.
+    pub similarity_threshold: f64,
+    /// Let the selector model choose among the top embedding matches.
+    pub rerank: bool,
+    /// Embedding index file; defaults to `~/GenAI/cache/skill-embeddings.json`.
+    pub index_path: Option<String>,
+}
+
+impl Default for SelectorConfig {
+    fn default() -> Self {
+        Self {
+            mode: SelectorMode::Llm,
+            confidence_threshold: 0.5,
+            top_k: 3,
+            model: String::new(),
+            min_score: 0.5,
+            embedding_model: "gemini-embedding-001".to_string(),
+            similarity_threshold: 0.7,
+            rerank: false,
+            index_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub skill: String,
+    pub confidence: Option<f64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectionMethod {
+    Llm,
+    Embedding,
+    User,
+    Fallback,
+}
+
+impl SelectionMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Llm => "llm",
+            Self::Embedding => "embedding",
+            Self::User => "user",
+            Self::Fallback => "fallback",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Selection<'a> {
+    pub skill: &'a Skill,
+    /// The model's confidence; `None` when the lexical fallback chose.
+    pub confidence: Option<f64>,
+    pub reason: String,
+    pub method: SelectionMethod,
+    /// The other candidates, best first.
+    pub runner_ups: Vec<Candidate>,
+}
+
+impl Selection<'_> {
+    /// Human-readable summary for `genai run --explain`.
+    pub fn explain(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "Selected: {} ({})",
+                self.skill.qualified_name(),
+                format_confidence(self.confidence)
+            ),
+            format!("Method:   {}", self.method.as_str()),
+            format!("Reason:   {}", self.reason),
+        ];
+        if !self.runner_ups.is_empty() {
+            lines.push("Runner-ups:".to_string());
+            lines.extend(self.runner_ups.iter().map(|c| {
+                format!(
+                    "  {} ({}) {}",
+                    c.skill,
+                    format_confidence(c.confidence),
+                    c.reason
+                )
+            }));
+        }
+        lines.join("\n")
+    }
+}
+
 pub fn select_skill<'a>(
     user_input: &str,
     skills: &'a [Skill],
     llm: Option<&dyn LlmClient>,
-) -> Result<&'a Skill> {
+    config: &SelectorConfig,
+    interactive: bool,
+) -> Result<Selection<'a>> {
     if skills.is_empty() {
         return Err(anyhow!("No skills found"));
     }
 
     if let Some(client) = llm {
-        let prompt = build_selector_prompt(user_input, skills);
-        if let Ok(resp) = client.generate("", &prompt) {
-            if let Ok(parsed) = serde_json::from_str::<SelectorResponse>(&resp) {
-                let _ = (parsed.confidence, &parsed.reason);
-                if let Some(skill) = skills.iter().find(|s| s.metadata.name == parsed.skill) {
-                    return Ok(skill);
-                }
+        let selection = match config.mode {
+            SelectorMode::Llm => {
+                let candidates = skills.iter().collect::<Vec<_>>();
+                llm_select(user_input, &candidates, client, config, interactive)?
             }
+            SelectorMode::Embedding => {
+                embedding_select(user_input, skills, client, config, interactive)?
+            }
+        };
+        if let Some(selection) = selection {
+            return Ok(selection);
         }
     }
 
-    fallback_select(user_input, skills)
+    fallback_select(user_input, skills, config)
 }
 
-fn fallback_select<'a>(user_input: &str, skills: &'a [Skill]) -> Result<&'a Skill> {
-    let input = user_input.to_lowercase();
+/// Asks the model to choose among `candidates`; `None` means fall back.
+fn llm_select<'a>(
+    user_input: &str,
+    candidates: &[&'a Skill],
+    client: &dyn LlmClient,
+    config: &SelectorConfig,
+    interactive: bool,
+) -> Result<Option<Selection<'a>>> {
+    let prompt = build_selector_prompt(user_input, candidates, config.top_k.max(1));
+    match client.generate(&config.model, &prompt) {
+        Ok(resp) => match parse_response(&resp, candidates, config.top_k.max(1)) {
+            Some(ranked) => decide(
+                user_input,
+                ranked,
+                config,
+                interactive,
+                SelectionMethod::Llm,
+            )
+            .map(Some),
+            None => {
+                warn!("Selector reply is not a usable JSON selection, using fallback");
+                Ok(None)
+            }
+        },
+        Err(err) => {
+            warn!("Selector request failed, using fallback: {err}");
+            Ok(None)
+        }
+    }
+}
 
-    let scored = skills
+/// Ranks skills by cosine similarity against the cached index, optionally letting the
+/// model rerank the shortlist; `None` means fall back.
+fn embedding_select<'a>(
+    user_input: &str,
+    skills: &'a [Skill],
+    client: &dyn LlmClient,
+    config: &SelectorConfig,
+    interactive: bool,
+) -> Result<Option<Selection<'a>>> {
+    let ranked = match embedding_rank(user_input, skills, client, config) {
+        Ok(ranked) if !ranked.is_empty() => ranked,
+        Ok(_) => return Ok(None),
+        Err(err) => {
+            warn!("Embedding selection failed, using fallback: {err}");
+            return Ok(None);
+        }
+    };
+    let shortlist = ranked
         .iter()
-        .map(|skill| {
-            let mut score = 0usize;
-            let mut tag_matches = 0usize;
+        .take(config.top_k.max(1))
+        .map(|(i, similarity)| {
+            (
+                &skills[*i],
+                similarity.clamp(0.0, 1.0),
+                format!("cosine similarity {similarity:.2}"),
+            )
+        })
+        .collect::<Vec<_>>();
 
-            if skill.metadata.description.to_lowercase().contains(&input) {
-                score += 2;
-            }
-            if skill.metadata.category.to_lowercase().contains(&input) {
-                score += 2;
-            }
+    if config.rerank {
+        let candidates = shortlist
+            .iter()
+            .map(|(skill, _, _)| *skill)
+            .collect::<Vec<_>>();
+        if let Some(selection) = llm_select(user_input, &candidates, client, config, interactive)? {
+            return Ok(Some(selection));
+        }
+    }
+    decide(
+        user_input,
+        shortlist,
+        config,
+        interactive,
+        SelectionMethod::Embedding,
+    )
+    .map(Some)
+}
 
-            for tag in &skill.metadata.tags {
-                if input.contains(&tag.to_lowercase()) || tag.to_lowercase().contains(&input) {
-                    score += 1;
-                    tag_matches += 1;
-                }
-            }
+fn embedding_rank(
+    user_input: &str,
+    skills: &[Skill],
+    client: &dyn LlmClient,
+    config: &SelectorConfig,
+) -> Result<Vec<(usize, f64)>> {
+    let path = match &config.index_path {
+        Some(path) => PathBuf::from(path),
+        None => EmbeddingIndex::default_path()?,
+    };
+    let mut index = EmbeddingIndex::load(&path);
+    if index.refresh(skills, client, &config.embedding_model)? > 0 {
+        index.save(&path)?;
+    }
+    let query = client
+        .embed(&config.embedding_model, &[user_input.to_string()])?
+        .pop()
+        .ok_or_else(|| anyhow!("Embedding returned no vector for the prompt"))?;
+    Ok(index.rank(skills, &query))
+}
 
-            (skill, score, tag_matches)
+/// Known skills from the reply, best first, at most `top_k`.
+fn parse_response<'a>(
+    response: &str,
+    skills: &[&'a Skill],
+    top_k: usize,
+) -> Option<Vec<(&'a Skill, f64, String)>> {
+    let parsed: SelectorResponse = serde_json::from_str(extract_json_object(response)?).ok()?;
+    let chosen = *skills.iter().find(|s| s.matches_name(&parsed.skill))?;
+
+    let mut ranked = vec![(chosen, parsed.confidence.clamp(0.0, 1.0), parsed.reason)];
+    for alternative in parsed.alternatives {
+        let Some(&skill) = skills.iter().find(|s| s.matches_name(&alternative.skill)) else {
+            continue;
+        };
+        if ranked.iter().any(|(s, _, _)| std::ptr::eq(*s, skill)) {
+            continue;
+        }
+        ranked.push((
+            skill,
+            alternative.confidence.clamp(0.0, 1.0),
+            alternative.reason,
+        ));
+    }
+    ranked[1..].sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(top_k);
+    Some(ranked)
+}
+
+fn decide<'a>(
+    user_input: &str,
+    ranked: Vec<(&'a Skill, f64, String)>,
+    config: &SelectorConfig,
+    interactive: bool,
+    method: SelectionMethod,
+) -> Result<Selection<'a>> {
+    let candidates = ranked
+        .iter()
+        .map(|(skill, confidence, reason)| Candidate {
+            skill: skill.qualified_name(),
+            confidence: Some(*confidence),
+            reason: reason.clone(),
         })
-        .max_by(|a, b| a.1.cmp(&b.1).then(a.2.cmp(&b.2)));
+        .collect::<Vec<_>>();
+
+    let threshold = match method {
+        SelectionMethod::Embedding => config.similarity_threshold,
+        _ => config.confidence_threshold,
+    };
+    let (index, method) = if ranked[0].1 >= threshold {
+        (0, method)
+    } else if interactive {
+        (prompt_user_choice(&candidates)?, SelectionMethod::User)
+    } else {
+        return Err(anyhow!(
+            "No confident skill match for '{user_input}' (best {:.2} < threshold {:.2}). \
+Candidates: {}. Use run-skill to choose one.",
+            ranked[0].1,
+            threshold,
+            candidates
+                .iter()
+                .map(|c| format!("{} ({})", c.skill, format_confidence(c.confidence)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    };
+
+    let mut runner_ups = candidates;
+    runner_ups.remove(index);
+    let (skill, confidence, reason) = ranked.into_iter().nth(index).expect("index is in range");
+    Ok(Selection {
+        skill,
+        confidence: Some(confidence),
+        reason,
+        method,
+        runner_ups,
+    })
+}
+
+fn prompt_user_choice(candidates: &[Candidate]) -> Result<usize> {
+    let mut stderr = std::io::stderr();
+    writeln!(stderr, "No confident match. Candidates:")?;
+    for (i, candidate) in candidates.iter().enumerate() {
+        writeln!(
+            stderr,
+            "  {}. {} ({}) {}",
+            i + 1,
+            candidate.skill,
+            format_confidence(candidate.confidence),
+            candidate.reason
+        )?;
+    }
+    loop {
+        write!(stderr, "Choose a skill [1-{}]: ", candidates.len())?;
+        stderr.flush()?;
+
+        let mut line = String::new();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            return Err(anyhow!("No skill chosen"));
+        }
+        if let Some(n) = line
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=candidates.len()).contains(n))
+        {
+            return Ok(n - 1);
+        }
+    }
+}
+
+fn format_confidence(confidence: Option<f64>) -> String {
+    confidence.map_or_else(|| "n/a".to_string(), |c| format!("{c:.2}"))
+}
 
-    scored
-        .map(|(skill, _, _)| skill)
-        .ok_or_else(|| anyhow!("Unable to select a skill"))
+/// Offline BM25 ranking; fails when nothing scores at least `min_score`.
+fn fallback_select<'a>(
+    user_input: &str,
+    skills: &'a [Skill],
+    config: &SelectorConfig,
+) -> Result<Selection<'a>> {
+    let ranked = LexicalIndex::new(skills).rank(user_input);
+    let mut candidates = ranked
+        .iter()
+        .take(config.top_k.max(1))
+        .map(|(i, score)| Candidate {
+            skill: skills[*i].qualified_name(),
+            confidence: None,
+            reason: format!("lexical score {score:.2}"),
+        });
+
+    match (ranked.first(), candidates.next()) {
+        (Some(&(index, score)), Some(best)) if score >= config.min_score => Ok(Selection {
+            skill: &skills[index],
+            confidence: None,
+            reason: best.reason,
+            method: SelectionMethod::Fallback,
+            runner_ups: candidates.collect(),
+        }),
+        (best, _) => Err(anyhow!(
+            "No skill matches '{user_input}' well enough (best lexical score {:.2} < {:.2}). \
+Use run-skill to choose one, or add examples to the intended skill.",
+            best.map_or(0.0, |(_, score)| *score),
+            config.min_score
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{select_skill, SelectionMethod, SelectorConfig};
+    use crate::llm::client::LlmClient;
+    use crate::util::testing::test_skill;
+
+    struct Scripted(&'static str);
+
+    impl LlmClient for Scripted {
+        fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[test]
+    fn reads_fenced_json_and_ranks_alternatives() {
+        let skills = vec![
+            test_skill("commit"),
+            test_skill("changelog"),
+            test_skill("review"),
+        ];
+        let llm = Scripted(
+            "```json\n{\"skill\":\"commit\",\"confidence\":0.9,\"reason\":\"staged changes\",\
+\"alternatives\":[{\"skill\":\"review\",\"confidence\":0.2},{\"skill\":\"changelog\",\"confidence\":0.4},\
+{\"skill\":\"unknown\",\"confidence\":0.8}]}\n```",
+        );
+
+        let selection = select_skill(
+            "commit this",
+            &skills,
+            Some(&llm),
+            &SelectorConfig::default(),
+            false,
+        )
+        .expect("selection should succeed");
+        assert_eq!(selection.skill.metadata.name, "commit");
+        assert_eq!(selection.method, SelectionMethod::Llm);
+        assert_eq!(selection.reason, "staged changes");
+        let runner_ups = selection
+            .runner_ups
+            .iter()
+            .map(|c| c.skill.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(runner_ups, vec!["changelog", "review"]);
+    }
+
+    #[test]
+    fn low_confidence_fails_when_not_interactive() {
+        let skills = vec![test_skill("commit"), test_skill("changelog")];
+        let llm = Scripted(r#"{"skill":"commit","confidence":0.3,"reason":"unsure"}"#);
+
+        let err = select_skill(
+            "hmm",
+            &skills,
+            Some(&llm),
+            &SelectorConfig::default(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("0.30 < threshold 0.50"), "{err}");
+    }
+
+    #[test]
+    fn reason_is_optional_and_a_missing_confidence_is_not_confident() {
+        let skills = vec![test_skill("commit"), test_skill("changelog")];
+        let config = SelectorConfig::default();
+
+        let llm = Scripted(r#"{"skill":"commit","confidence":0.9}"#);
+        let selection = select_skill("commit this", &skills, Some(&llm), &config, false).unwrap();
+        assert_eq!(selection.method, SelectionMethod::Llm);
+        assert_eq!(selection.reason, "");
+
+        let llm = Scripted(r#"{"skill":"commit","reason":"staged changes"}"#);
+        let err = select_skill("commit this", &skills, Some(&llm), &config, false).unwrap_err();
+        assert!(err.to_string().contains("0.00 < threshold 0.50"), "{err}");
+    }
 }
@@ -1,80 +1,469 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, Result};
+use regex::Regex;
 
-use crate::skill::model::{Skill, StepType};
+use crate::skill::catalog::find_skill;
+use crate::skill::dataflow::analyze_dataflow;
+use crate::skill::diagnostics::{field, step, Diagnostic, Severity};
+use crate::skill::inputs::{input_env_name, normalize, BUILTIN_VARS};
+use crate::skill::model::{InputSpec, InputType, RoutingPolicy, Skill, StepType, WorkflowStep};
 
+/// Fails with the first error `skill_diagnostics` reports.
 pub fn validate_skill(skill: &Skill) -> Result<()> {
+    match skill_diagnostics(skill)
+        .into_iter()
+        .find(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        Some(error) => Err(anyhow!(error.message)),
+        None => Ok(()),
+    }
+}
+
+/// Every problem in a parsed skill, in metadata then step order. Diagnostics carry a
+/// target but no position; `check_skill_file` resolves them against the source.
+pub fn skill_diagnostics(skill: &Skill) -> Vec<Diagnostic> {
     let metadata = &skill.metadata;
+    let mut diagnostics = Vec::new();
 
     if metadata.name.trim().is_empty() {
-        return Err(anyhow!("Skill name cannot be empty"));
+        diagnostics.push(Diagnostic::error(
+            "empty-name",
+            field("name"),
+            "Skill name cannot be empty",
+        ));
+    }
+    if let Err(err) = metadata.semver() {
+        diagnostics.push(Diagnostic::error(
+            "invalid-version",
+            field("version"),
+            format!(
+                "Skill version '{}' is not semver (e.g. 1.2.0): {err}",
+                metadata.version
+            ),
+        ));
     }
     if metadata.entrypoint != "workflow" {
-        return Err(anyhow!("Only entrypoint=workflow is supported"));
+        diagnostics.push(Diagnostic::error(
+            "unsupported-entrypoint",
+            field("entrypoint"),
+            "Only entrypoint=workflow is supported",
+        ));
     }
     if metadata.workflow_version != 1 {
-        return Err(anyhow!("Only workflow_version=1 is supported"));
+        diagnostics.push(Diagnostic::error(
+            "unsupported-workflow-version",
+            field("workflow_version"),
+            "Only workflow_version=1 is supported",
+        ));
     }
 
+    validate_inputs(&metadata.inputs, &mut diagnostics);
+
     let mut ids = HashSet::new();
 
-    for step in &skill.steps {
-        if !ids.insert(step.id.clone()) {
-            return Err(anyhow!("Duplicate step id: {}", step.id));
+    for current in &skill.steps {
+        let error = |code, key: Option<&str>, message: String| {
+            Diagnostic::error(code, step(&current.id, key), message)
+        };
+
+        if !ids.insert(current.id.clone()) {
+            diagnostics.push(error(
+                "duplicate-step-id",
+                Some("id"),
+                format!("Duplicate step id: {}", current.id),
+            ));
+        }
+
+        if let Some(sampling) = &current.sampling {
+            if !matches!(current.step_type, StepType::Llm) {
+                diagnostics.push(error(
+                    "misplaced-field",
+                    Some("sampling"),
+                    format!("Step '{}' uses sampling but is not an llm step", current.id),
+                ));
+            }
+            if sampling.n == 0 {
+                diagnostics.push(
+                    error(
+                        "invalid-sampling",
+                        Some("sampling"),
+                        format!("Step '{}' sampling.n must be >= 1", current.id),
+                    )
+                    .with_needle("n:"),
+                );
+            }
+            if let Some(pattern) = &sampling.pattern {
+                if let Err(err) = Regex::new(pattern) {
+                    diagnostics.push(
+                        error(
+                            "invalid-sampling",
+                            Some("sampling"),
+                            format!("Step '{}' has invalid sampling pattern: {err}", current.id),
+                        )
+                        .with_needle("pattern:"),
+                    );
+                }
+            }
         }
 
-        match step.step_type {
+        if current.thinking_budget.is_some() || current.include_thoughts.is_some() {
+            let key = if current.thinking_budget.is_some() {
+                "thinking_budget"
+            } else {
+                "include_thoughts"
+            };
+            if !matches!(current.step_type, StepType::Llm) {
+                diagnostics.push(error(
+                    "misplaced-field",
+                    Some(key),
+                    format!(
+                        "Step '{}' sets thinking options but is not an llm step",
+                        current.id
+                    ),
+                ));
+            }
+            if current.thinking_budget.is_some_and(|budget| budget < -1) {
+                diagnostics.push(error(
+                    "invalid-thinking",
+                    Some("thinking_budget"),
+                    format!(
+                        "Step '{}' thinking_budget must be -1 (dynamic), 0 (off) or positive",
+                        current.id
+                    ),
+                ));
+            }
+        }
+
+        let is_skill_step = matches!(current.step_type, StepType::Skill);
+        if !is_skill_step
+            && (current.skill.is_some() || !current.with.is_empty() || !current.outputs.is_empty())
+        {
+            let key = if current.skill.is_some() {
+                "skill"
+            } else if !current.with.is_empty() {
+                "with"
+            } else {
+                "outputs"
+            };
+            diagnostics.push(error(
+                "misplaced-field",
+                Some(key),
+                format!(
+                    "Step '{}' sets skill, with or outputs but is not a skill step",
+                    current.id
+                ),
+            ));
+        }
+
+        if current.routing.is_some() && !matches!(current.step_type, StepType::Llm) {
+            diagnostics.push(error(
+                "misplaced-field",
+                Some("routing"),
+                format!("Step '{}' sets routing but is not an llm step", current.id),
+            ));
+        }
+
+        match current.step_type {
             StepType::Command => {
                 if !metadata.permissions.run_commands {
-                    return Err(anyhow!(
-                        "Command step '{}' not allowed when run_commands=false",
-                        step.id
+                    diagnostics.push(error(
+                        "command-not-allowed",
+                        Some("type"),
+                        format!(
+                            "Command step '{}' not allowed when run_commands=false",
+                            current.id
+                        ),
                     ));
                 }
-                let runner = step
-                    .runner
-                    .as_deref()
-                    .ok_or_else(|| anyhow!("Command step '{}' missing runner", step.id))?;
-                if !metadata
-                    .permissions
-                    .allowed_runners
-                    .iter()
-                    .any(|r| r == runner)
-                {
-                    return Err(anyhow!(
-                        "Command step '{}' runner '{}' not in allowed_runners",
-                        step.id,
-                        runner
-                    ));
+                match current.runner.as_deref() {
+                    None => diagnostics.push(error(
+                        "missing-runner",
+                        None,
+                        format!("Command step '{}' missing runner", current.id),
+                    )),
+                    Some(runner)
+                        if !metadata
+                            .permissions
+                            .allowed_runners
+                            .iter()
+                            .any(|r| r == runner) =>
+                    {
+                        diagnostics.push(error(
+                            "runner-not-allowed",
+                            Some("runner"),
+                            format!(
+                                "Command step '{}' runner '{}' not in allowed_runners",
+                                current.id, runner
+                            ),
+                        ))
+                    }
+                    Some(_) => {}
                 }
-                if step.cmd.as_deref().unwrap_or_default().trim().is_empty() {
-                    return Err(anyhow!("Command step '{}' has empty cmd", step.id));
+                if current.cmd.as_deref().unwrap_or_default().trim().is_empty() {
+                    diagnostics.push(error(
+                        "empty-command",
+                        Some("cmd"),
+                        format!("Command step '{}' has empty cmd", current.id),
+                    ));
                 }
             }
             StepType::Llm => {
-                if !metadata.permissions.network_access {
-                    let model = step.model.as_deref().unwrap_or_default();
-                    if model != "executor" {
-                        return Err(anyhow!(
-                            "network_access=false requires offline/mock model='executor' for step '{}'",
-                            step.id
-                        ));
+                let models: Vec<&str> = match &current.routing {
+                    Some(routing) => {
+                        validate_routing(current, routing, &mut diagnostics);
+                        routing.rules.iter().map(|r| r.model.as_str()).collect()
                     }
+                    None => vec![current.model.as_deref().unwrap_or_default()],
+                };
+                let judge = current
+                    .sampling
+                    .as_ref()
+                    .and_then(|s| s.judge_model.as_deref())
+                    .filter(|model| *model != "executor");
+                if !metadata.permissions.network_access
+                    && (judge.is_some() || models.iter().any(|model| *model != "executor"))
+                {
+                    let key = if models.iter().all(|model| *model == "executor") {
+                        "sampling"
+                    } else if current.routing.is_some() {
+                        "routing"
+                    } else {
+                        "model"
+                    };
+                    diagnostics.push(error(
+                        "network-not-allowed",
+                        Some(key),
+                        format!(
+                            "network_access=false requires offline/mock model='executor' for step '{}'",
+                            current.id
+                        ),
+                    ));
                 }
             }
             StepType::Output => {}
+            StepType::Skill => {
+                let target = current.skill.as_deref().unwrap_or_default();
+                if target.trim().is_empty() {
+                    diagnostics.push(error(
+                        "missing-skill",
+                        None,
+                        format!("Skill step '{}' missing skill", current.id),
+                    ));
+                } else if skill.matches_name(target) {
+                    diagnostics.push(error(
+                        "self-call",
+                        Some("skill"),
+                        format!("Skill step '{}' calls its own skill", current.id),
+                    ));
+                }
+            }
         }
     }
 
+    diagnostics.extend(analyze_dataflow(skill));
+    diagnostics
+}
+
+/// Unused outputs and `input_vars` mismatches; these do not stop a skill from loading.
+pub fn dataflow_warnings(skill: &Skill) -> Vec<String> {
+    analyze_dataflow(skill)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+        .map(|warning| format!("Skill '{}': {}", skill.qualified_name(), warning.message))
+        .collect()
+}
+
+/// Checks `type: skill` steps across a set of skills: every callee exists, is allowed
+/// to run with the caller's permissions, and no skill reaches itself through calls.
+pub fn validate_skill_calls(skills: &[Skill]) -> Result<()> {
+    skills
+        .iter()
+        .try_for_each(|skill| validate_calls(skill, skills))
+}
+
+/// [`validate_skill_calls`] for the steps of one skill.
+pub fn validate_calls(skill: &Skill, skills: &[Skill]) -> Result<()> {
+    for (step, callee) in callees(skill, skills)? {
+        let mut effective = callee.clone();
+        effective.metadata.permissions = skill
+            .metadata
+            .permissions
+            .intersect(&callee.metadata.permissions);
+        validate_skill(&effective).map_err(|err| {
+            anyhow!(
+                "Skill step '{}' in '{}' calls '{}', which needs more permissions: {err}",
+                step.id,
+                skill.qualified_name(),
+                callee.qualified_name()
+            )
+        })?;
+    }
+    find_cycle(skill, skills, &mut vec![skill.qualified_name()])
+}
+
+fn callees<'a>(
+    skill: &'a Skill,
+    skills: &'a [Skill],
+) -> Result<Vec<(&'a WorkflowStep, &'a Skill)>> {
+    skill
+        .steps
+        .iter()
+        .filter_map(|step| step.skill.as_deref().map(|target| (step, target)))
+        .map(|(step, target)| {
+            find_skill(skills, target)
+                .map(|callee| (step, callee))
+                .map_err(|err| {
+                    anyhow!(
+                        "Skill step '{}' in '{}': {err}",
+                        step.id,
+                        skill.qualified_name()
+                    )
+                })
+        })
+        .collect()
+}
+
+fn find_cycle(skill: &Skill, skills: &[Skill], path: &mut Vec<String>) -> Result<()> {
+    for (_, callee) in callees(skill, skills)? {
+        let name = callee.qualified_name();
+        if path.contains(&name) {
+            return Err(anyhow!("Skill call cycle: {} -> {name}", path.join(" -> ")));
+        }
+        path.push(name);
+        find_cycle(callee, skills, path)?;
+        path.pop();
+    }
     Ok(())
 }
 
+fn validate_inputs(inputs: &[InputSpec], diagnostics: &mut Vec<Diagnostic>) {
+    let mut env_names = HashMap::new();
+    for input in inputs {
+        let mut error = |message: String| {
+            diagnostics.push(
+                Diagnostic::error("invalid-input", field("inputs"), message)
+                    .with_needle(input.name.as_str()),
+            )
+        };
+        if input.name.is_empty()
+            || !input
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            error(format!(
+                "Input name '{}' may only contain letters, digits, '_' and '-'",
+                input.name
+            ));
+        }
+        if BUILTIN_VARS.contains(&input.name.as_str()) {
+            error(format!(
+                "Input '{}' shadows a built-in variable",
+                input.name
+            ));
+        }
+        let env_name = input_env_name(&input.name);
+        match env_names.get(&env_name) {
+            Some(first) if *first == input.name.as_str() => {
+                error(format!("Duplicate input: {}", input.name));
+            }
+            Some(first) => error(format!(
+                "Inputs '{first}' and '{}' both map to ${env_name}",
+                input.name
+            )),
+            None => {
+                env_names.insert(env_name, input.name.as_str());
+            }
+        }
+        match input.input_type {
+            InputType::Enum if input.values.is_empty() => {
+                error(format!("Enum input '{}' must list its values", input.name));
+            }
+            InputType::Enum => {}
+            _ if !input.values.is_empty() => {
+                error(format!(
+                    "Input '{}' lists values but is not an enum",
+                    input.name
+                ));
+            }
+            _ => {}
+        }
+        if let Some(default) = &input.default {
+            if let Err(err) = normalize(input, &default.to_raw()) {
+                error(format!("Invalid default: {err}"));
+            }
+        }
+    }
+}
+
+fn validate_routing(
+    current: &WorkflowStep,
+    routing: &RoutingPolicy,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let target = step(&current.id, Some("routing"));
+    if routing.rules.is_empty() {
+        diagnostics.push(Diagnostic::error(
+            "invalid-routing",
+            target,
+            format!("Step '{}' routing has no rules", current.id),
+        ));
+    } else if routing
+        .rules
+        .windows(2)
+        .any(|pair| pair[0].below >= pair[1].below)
+    {
+        diagnostics.push(Diagnostic::error(
+            "invalid-routing",
+            target,
+            format!(
+                "Step '{}' routing rules must have strictly increasing 'below' bounds",
+                current.id
+            ),
+        ));
+    }
+}
+
+/// Models referenced by llm steps that are missing from `known_models`. Built-in
+/// pseudo-models (`executor`, `selector`) and the empty provider default are ignored.
+pub fn unknown_model_warnings(skill: &Skill, known_models: &[String]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for step in &skill.steps {
+        let routed = step.routing.iter().flat_map(|r| &r.rules);
+        let models = [
+            step.model.as_deref(),
+            step.sampling
+                .as_ref()
+                .and_then(|s| s.judge_model.as_deref()),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(routed.map(|rule| rule.model.as_str()));
+        for model in models {
+            let model = model.trim();
+            if model.is_empty() || model == "executor" || model == "selector" {
+                continue;
+            }
+            if !known_models.iter().any(|known| known == model) {
+                warnings.push(format!(
+                    "Skill '{}' step '{}' references model '{}' which no configured provider lists",
+                    skill.metadata.name, step.id, model
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
 #[cfg(test)]
 mod tests {
-    use super::validate_skill;
+    use super::{unknown_model_warnings, validate_skill, validate_skill_calls};
     use crate::skill::model::{
-        Capabilities, Permissions, ResponseFormat, Skill, SkillMetadata, StepType, WorkflowStep,
+        Capabilities, InputSpec, InputType, InputValue, Permissions, ResponseFormat, RoutingPolicy,
+        RoutingRule, Sampling, SamplingStrategy, Skill, SkillMetadata, StepType, WorkflowStep,
     };
 
     #[test]
@@ -92,6 +481,7 @@ mod tests {
                 prompt: None,
                 format: None,
                 template: None,
+                ..Default::default()
             },
             WorkflowStep {
                 id: "fallback_unstaged".to_string(),
@@ -105,6 +495,7 @@ mod tests {
                 prompt: None,
                 format: None,
                 template: None,
+                ..Default::default()
             },
         ]);
 
@@ -130,6 +521,7 @@ mod tests {
                 prompt: None,
                 format: Some("text".to_string()),
                 template: Some("one".to_string()),
+                ..Default::default()
             },
             WorkflowStep {
                 id: "duplicate".to_string(),
@@ -143,6 +535,7 @@ mod tests {
                 prompt: None,
                 format: Some("text".to_string()),
                 template: Some("two".to_string()),
+                ..Default::default()
             },
         ]);
 
@@ -150,6 +543,151 @@ mod tests {
         assert!(result.is_err(), "expected validation error");
     }
 
+    #[test]
+    fn rejects_sampling_on_non_llm_steps() {
+        let skill = base_skill(vec![WorkflowStep {
+            id: "respond".to_string(),
+            step_type: StepType::Output,
+            template: Some("hi".to_string()),
+            sampling: Some(Sampling {
+                n: 3,
+                strategy: SamplingStrategy::Majority,
+                judge_prompt: None,
+                judge_model: None,
+                pattern: None,
+            }),
+            ..Default::default()
+        }]);
+
+        assert!(validate_skill(&skill).is_err());
+    }
+
+    #[test]
+    fn rejects_an_online_judge_in_an_offline_skill() {
+        let mut skill = base_skill(vec![WorkflowStep {
+            id: "generate".to_string(),
+            step_type: StepType::Llm,
+            model: Some("executor".to_string()),
+            prompt: Some("hi".to_string()),
+            sampling: Some(Sampling {
+                n: 3,
+                strategy: SamplingStrategy::Judge,
+                judge_prompt: None,
+                judge_model: Some("gemini-2.5-flash".to_string()),
+                pattern: None,
+            }),
+            ..Default::default()
+        }]);
+        skill.metadata.permissions.network_access = false;
+
+        let err = validate_skill(&skill).unwrap_err();
+        assert!(err.to_string().contains("network_access=false"), "{err:#}");
+    }
+
+    #[test]
+    fn warns_about_models_no_provider_lists() {
+        let skill = base_skill(vec![WorkflowStep {
+            id: "generate".to_string(),
+            step_type: StepType::Llm,
+            model: Some("gemini-9-ultra".to_string()),
+            prompt: Some("hi".to_string()),
+            ..Default::default()
+        }]);
+
+        let warnings = unknown_model_warnings(&skill, &["gemini-2.5-flash".to_string()]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("gemini-9-ultra"));
+        assert!(unknown_model_warnings(&skill, &["gemini-9-ultra".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn rejects_unordered_routing_rules() {
+        let skill = base_skill(vec![WorkflowStep {
+            id: "generate".to_string(),
+            step_type: StepType::Llm,
+            prompt: Some("{{diff}}".to_string()),
+            routing: Some(RoutingPolicy {
+                measure: Some("diff".to_string()),
+                rules: vec![
+                    RoutingRule {
+                        below: 200_000,
+                        model: "smart".to_string(),
+                    },
+                    RoutingRule {
+                        below: 4_000,
+                        model: "fast".to_string(),
+                    },
+                ],
+            }),
+            ..Default::default()
+        }]);
+
+        assert!(validate_skill(&skill).is_err());
+    }
+
+    #[test]
+    fn rejects_defaults_that_do_not_match_their_type() {
+        let mut skill = base_skill(vec![]);
+        skill.metadata.inputs = vec![InputSpec {
+            name: "format".to_string(),
+            input_type: InputType::Enum,
+            default: Some(InputValue::String("html".to_string())),
+            required: false,
+            description: None,
+            values: vec!["md".to_string(), "json".to_string()],
+        }];
+        assert!(validate_skill(&skill).is_err());
+
+        skill.metadata.inputs[0].default = Some(InputValue::String("md".to_string()));
+        assert!(validate_skill(&skill).is_ok());
+
+        skill.metadata.inputs[0].name = "user_input".to_string();
+        assert!(validate_skill(&skill).is_err());
+    }
+
+    #[test]
+    fn rejects_inputs_that_share_an_environment_variable() {
+        let mut skill = base_skill(vec![]);
+        let input = |name: &str| InputSpec {
+            name: name.to_string(),
+            input_type: InputType::String,
+            default: None,
+            required: false,
+            description: None,
+            values: vec![],
+        };
+        skill.metadata.inputs = vec![input("diff-base"), input("tag"), input("diff_base")];
+        let err = validate_skill(&skill).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Inputs 'diff-base' and 'diff_base' both map to $GENAI_INPUT_DIFF_BASE"
+        );
+
+        skill.metadata.inputs = vec![input("tag"), input("Tag")];
+        assert!(validate_skill(&skill).is_err());
+    }
+
+    #[test]
+    fn rejects_indirect_skill_call_cycles() {
+        let call = |id: &str, target: &str| WorkflowStep {
+            id: id.to_string(),
+            step_type: StepType::Skill,
+            skill: Some(target.to_string()),
+            ..Default::default()
+        };
+        let mut a = base_skill(vec![call("to-b", "b")]);
+        a.metadata.name = "a".to_string();
+        let mut b = base_skill(vec![call("to-c", "c")]);
+        b.metadata.name = "b".to_string();
+        let mut c = base_skill(vec![]);
+        c.metadata.name = "c".to_string();
+        assert!(validate_skill_calls(&[a.clone(), b.clone(), c.clone()]).is_ok());
+
+        c.steps = vec![call("to-a", "a")];
+        let err = validate_skill_calls(&[a, b, c]).unwrap_err();
+        assert_eq!(err.to_string(), "Skill call cycle: a -> b -> c -> a");
+    }
+
     fn base_skill(steps: Vec<WorkflowStep>) -> Skill {
         Skill {
             metadata: SkillMetadata {
@@ -175,10 +713,13 @@ mod tests {
                     format_type: "text".to_string(),
                     style: None,
                 },
+                inputs: vec![],
+                examples: vec![],
             },
             markdown_body: String::new(),
             steps,
             path: "skills/auto-commit-msg/SKILL.md".to_string(),
+            namespace: String::new(),
         }
     }
 }
@@ -0,0 +1,52 @@
+/// Finds the JSON object in a model reply that may wrap it in a ```json fence or prose.
+/// Returns the first balanced `{...}`, honouring strings and escapes.
+pub fn extract_json_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + offset + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_json_object;
+
+    #[test]
+    fn finds_objects_inside_fences_and_prose() {
+        let fenced = "```json\n{\"skill\": \"a\", \"reason\": \"uses {braces} and \\\"quotes\\\"\"}\n```";
+        assert_eq!(
+            extract_json_object(fenced),
+            Some("{\"skill\": \"a\", \"reason\": \"uses {braces} and \\\"quotes\\\"\"}")
+        );
+        assert_eq!(
+            extract_json_object("Sure! {\"a\": {\"b\": 1}} hope that helps"),
+            Some("{\"a\": {\"b\": 1}}")
+        );
+        assert_eq!(extract_json_object("no json here"), None);
+        assert_eq!(extract_json_object("{\"unterminated\": 1"), None);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod errors;
 pub mod fs;
+pub mod json;
 pub mod templating;
+#[cfg(test)]
+pub mod testing;
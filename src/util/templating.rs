@@ -3,11 +3,26 @@ use std::collections::HashMap;
 use anyhow::Result;
 use regex::Regex;
 
+const VARIABLE_PATTERN: &str = r"\{\{\s*([a-zA-Z0-9_\-]+)\s*\}\}";
+
 pub fn render_template(input: &str, context: &HashMap<String, String>) -> Result<String> {
-    let re = Regex::new(r"\{\{\s*([a-zA-Z0-9_\-]+)\s*\}\}")?;
+    let re = Regex::new(VARIABLE_PATTERN)?;
     let rendered = re.replace_all(input, |caps: &regex::Captures| {
         let key = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
         context.get(key).cloned().unwrap_or_default()
     });
     Ok(rendered.into_owned())
 }
+
+/// Names of the `{{variables}}` in `input`, in order of appearance, without duplicates.
+pub fn template_vars(input: &str) -> Vec<String> {
+    let re = Regex::new(VARIABLE_PATTERN).expect("variable pattern is valid");
+    let mut vars: Vec<String> = Vec::new();
+    for caps in re.captures_iter(input) {
+        let name = &caps[1];
+        if !vars.iter().any(|v| v == name) {
+            vars.push(name.to_string());
+        }
+    }
+    vars
+}
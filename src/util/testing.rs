@@ -0,0 +1,86 @@
+//! Fixtures shared by the unit tests.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use crate::skill::model::{Capabilities, Permissions, ResponseFormat, Skill, SkillMetadata};
+
+/// A text-format workflow skill with no steps, inputs or permissions.
+pub fn test_skill(name: &str) -> Skill {
+    Skill {
+        metadata: SkillMetadata {
+            name: name.to_string(),
+            description: "d".to_string(),
+            version: "1.0.0".to_string(),
+            category: "test".to_string(),
+            tags: vec![],
+            entrypoint: "workflow".to_string(),
+            workflow_version: 1,
+            capabilities: Capabilities {
+                requires_repo: false,
+                supports_interactive: false,
+            },
+            permissions: Permissions {
+                run_commands: false,
+                allowed_runners: vec![],
+                allowed_paths: vec![],
+                network_access: false,
+                write_access: false,
+            },
+            response_format: ResponseFormat {
+                format_type: "text".to_string(),
+                style: None,
+            },
+            inputs: vec![],
+            examples: vec![],
+        },
+        markdown_body: String::new(),
+        steps: vec![],
+        path: "SKILL.md".to_string(),
+        namespace: String::new(),
+    }
+}
+
+/// SKILL.md text for the same skill as [`test_skill`], with a `# name` body.
+pub fn skill_md(name: &str, description: &str, version: &str) -> String {
+    format!(
+        "---\nname: {name}\ndescription: {description}\nversion: {version}\ncategory: test\ntags: []\n\
+entrypoint: workflow\nworkflow_version: 1\n\
+capabilities: {{ requires_repo: false, supports_interactive: false }}\n\
+permissions: {{ run_commands: false, allowed_runners: [], allowed_paths: [], network_access: false, write_access: false }}\n\
+response_format: {{ type: text }}\n---\n# {name}\n"
+    )
+}
+
+/// Writes [`skill_md`] to `dir/SKILL.md`, creating `dir`.
+pub fn write_skill(dir: &Path, name: &str, description: &str, version: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join("SKILL.md"), skill_md(name, description, version)).unwrap();
+}
+
+/// An empty directory under the system temp dir, removed on drop.
+pub struct TempRoot(PathBuf);
+
+impl TempRoot {
+    /// `label` must be unique across the test binary.
+    pub fn new(label: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("genai-{label}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempRoot(dir)
+    }
+}
+
+impl Deref for TempRoot {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempRoot {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
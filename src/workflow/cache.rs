@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+
+/// Runner, command and the environment it runs with.
+type CommandKey = (String, String, Vec<(String, String)>);
+
+/// Command outputs keyed by runner, command and environment, shared between runs of
+/// the same skill.
+#[derive(Debug, Clone, Default)]
+pub struct CommandCache {
+    outputs: Arc<Mutex<HashMap<CommandKey, String>>>,
+}
+
+impl CommandCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Holds the lock while `run` executes so concurrent callers wait for the first result
+    /// instead of running the command again.
+    pub fn get_or_run(
+        &self,
+        runner: &str,
+        cmd: &str,
+        env: &[(String, String)],
+        run: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        let mut outputs = self
+            .outputs
+            .lock()
+            .map_err(|_| anyhow!("Command cache poisoned"))?;
+        let key = (runner.to_string(), cmd.to_string(), env.to_vec());
+        if let Some(output) = outputs.get(&key) {
+            return Ok(output.clone());
+        }
+
+        let output = run()?;
+        outputs.insert(key, output.clone());
+        Ok(output)
+    }
+}
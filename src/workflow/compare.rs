@@ -0,0 +1,200 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::llm::client::{LlmClient, Usage};
+use crate::skill::model::Skill;
+use crate::workflow::cache::CommandCache;
+use crate::workflow::executor::{ExecutionInput, ExecutionOptions, WorkflowExecutor};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareResult {
+    pub model: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u128,
+    pub usage: Usage,
+}
+
+/// Runs `skill` once per model on its own thread. Command steps share one cache, so a
+/// `git diff` is captured once and every model sees the same input.
+pub fn compare_models(
+    skill: &Skill,
+    models: &[String],
+    input: &ExecutionInput,
+    options: &ExecutionOptions,
+    client_factory: &(dyn Fn() -> Box<dyn LlmClient> + Sync),
+) -> Vec<CompareResult> {
+    let cache = CommandCache::new();
+
+    std::thread::scope(|scope| {
+        let handles = models
+            .iter()
+            .map(|model| {
+                let options = ExecutionOptions {
+                    model_override: Some(model.clone()),
+                    command_cache: Some(cache.clone()),
+                    ..options.clone()
+                };
+                scope.spawn(move || run_one(skill, model, input.clone(), options, client_factory))
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .zip(models)
+            .map(|(handle, model)| {
+                handle.join().unwrap_or_else(|_| CompareResult {
+                    model: model.clone(),
+                    output: None,
+                    error: Some("run panicked".to_string()),
+                    latency_ms: 0,
+                    usage: Usage::default(),
+                })
+            })
+            .collect()
+    })
+}
+
+fn run_one(
+    skill: &Skill,
+    model: &str,
+    input: ExecutionInput,
+    options: ExecutionOptions,
+    client_factory: &(dyn Fn() -> Box<dyn LlmClient> + Sync),
+) -> CompareResult {
+    let mut executor = WorkflowExecutor::with_options(client_factory(), options);
+    let result = executor.execute(skill, input);
+    let trace = executor.trace();
+
+    let (output, error) = match result {
+        Ok(output) => (Some(output), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+    CompareResult {
+        model: model.to_string(),
+        output,
+        error,
+        latency_ms: trace.duration_ms,
+        usage: trace.usage,
+    }
+}
+
+pub fn render_table(results: &[CompareResult]) -> String {
+    let mut lines = vec![format!(
+        "{:<28} {:>10} {:>8} {:>8} {:>8}  {}",
+        "MODEL", "LATENCY", "PROMPT", "OUTPUT", "THINK", "RESULT"
+    )];
+
+    for result in results {
+        let summary = match (&result.output, &result.error) {
+            (_, Some(error)) => format!("ERROR: {error}"),
+            (Some(output), None) => output.trim().lines().next().unwrap_or_default().to_string(),
+            (None, None) => String::new(),
+        };
+        lines.push(format!(
+            "{:<28} {:>8}ms {:>8} {:>8} {:>8}  {}",
+            result.model,
+            result.latency_ms,
+            result.usage.prompt_tokens,
+            result.usage.output_tokens,
+            result.usage.thinking_tokens,
+            summary
+        ));
+    }
+
+    lines.join("\n")
+}
+
+pub fn render_json(results: &[CompareResult]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(results)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::compare_models;
+    use crate::llm::client::LlmClient;
+    use crate::llm::mock::MockLlmClient;
+    use crate::skill::model::{StepType, WorkflowStep};
+    use crate::util::testing::{test_skill, TempRoot};
+    use crate::workflow::executor::{ExecutionInput, ExecutionOptions};
+
+    static CLIENTS_BUILT: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn runs_every_model_and_captures_commands_once() {
+        let root = TempRoot::new("compare");
+        let marker = root.join("runs");
+
+        let mut skill = test_skill("compare-test");
+        let permissions = &mut skill.metadata.permissions;
+        permissions.run_commands = true;
+        permissions.allowed_runners = vec!["bash".to_string()];
+        permissions.network_access = true;
+        skill.steps = vec![
+            WorkflowStep {
+                id: "capture".to_string(),
+                step_type: StepType::Command,
+                runner: Some("bash".to_string()),
+                cmd: Some(format!(
+                    "echo run >> {0}; cat {0} | wc -l",
+                    marker.display()
+                )),
+                output_var: Some("runs".to_string()),
+                ..Default::default()
+            },
+            WorkflowStep {
+                id: "generate".to_string(),
+                step_type: StepType::Llm,
+                model: Some("ignored".to_string()),
+                prompt: Some("{{runs}}".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let factory = || {
+            CLIENTS_BUILT.fetch_add(1, Ordering::SeqCst);
+            Box::new(MockLlmClient::new()) as Box<dyn LlmClient>
+        };
+        let models = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let input = ExecutionInput {
+            user_prompt: String::new(),
+            debug: false,
+            interactive: false,
+            inputs: Default::default(),
+        };
+
+        let results = compare_models(
+            &skill,
+            &models,
+            &input,
+            &ExecutionOptions::default(),
+            &factory,
+        );
+
+        assert_eq!(CLIENTS_BUILT.load(Ordering::SeqCst), 3);
+        for (result, model) in results.iter().zip(&models) {
+            assert_eq!(&result.model, model);
+            assert_eq!(
+                result.output.as_deref().map(str::trim),
+                Some(format!("[mock:{model}] 1").as_str())
+            );
+        }
+
+        skill.metadata.permissions.network_access = false;
+        skill.steps[1].model = Some("executor".to_string());
+        let results = compare_models(
+            &skill,
+            &models[..1],
+            &input,
+            &ExecutionOptions::default(),
+            &factory,
+        );
+        let _ = std::fs::remove_file(&marker);
+        assert!(results[0]
+            .output
+            .as_deref()
+            .is_some_and(|output| !output.starts_with("[mock:a]")));
+    }
+}
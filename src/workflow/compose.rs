@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Context, Result};
+
+use crate::llm::client::LlmClient;
+use crate::skill::catalog::find_skill;
+use crate::skill::model::{Skill, WorkflowStep};
+use crate::skill::validator::validate_skill;
+use crate::util::templating::render_template;
+use crate::workflow::context::ExecutionContext;
+use crate::workflow::executor::{initial_context, run_workflow, ExecutionInput, ExecutionOptions};
+use crate::workflow::trace::{ExecutionTrace, StepTrace};
+
+/// Runs the skill named by a `type: skill` step and returns its final output.
+///
+/// The callee sees the caller's `user_input` plus the rendered `with` inputs, runs with
+/// the intersection of both skills' permissions, and its trace is nested under the step.
+pub fn call_skill(
+    caller: &Skill,
+    step: &WorkflowStep,
+    ctx: &mut ExecutionContext,
+    llm: &dyn LlmClient,
+    options: &ExecutionOptions,
+    trace: &mut StepTrace,
+) -> Result<String> {
+    let reference = step
+        .skill
+        .as_deref()
+        .ok_or_else(|| anyhow!("Skill step '{}' missing skill", step.id))?;
+    let mut callee = find_skill(&options.skills, reference)?.clone();
+
+    let mut call_stack = options.call_stack.clone();
+    if call_stack.is_empty() {
+        call_stack.push(caller.qualified_name());
+    }
+    if call_stack.contains(&callee.qualified_name()) {
+        return Err(anyhow!(
+            "Skill call cycle: {} -> {}",
+            call_stack.join(" -> "),
+            callee.qualified_name()
+        ));
+    }
+    call_stack.push(callee.qualified_name());
+
+    callee.metadata.permissions = caller
+        .metadata
+        .permissions
+        .intersect(&callee.metadata.permissions);
+    validate_skill(&callee).with_context(|| {
+        format!(
+            "Skill '{}' needs more permissions than '{}' grants",
+            callee.qualified_name(),
+            caller.qualified_name()
+        )
+    })?;
+
+    let inputs = step
+        .with
+        .iter()
+        .map(|(name, template)| Ok((name.clone(), render_template(template, ctx.as_map())?)))
+        .collect::<Result<_>>()?;
+    let input = ExecutionInput {
+        user_prompt: ctx.get("user_input").cloned().unwrap_or_default(),
+        debug: ctx.get("debug").is_some_and(|v| v == "true"),
+        interactive: ctx.get("interactive").is_some_and(|v| v == "true"),
+        inputs,
+    };
+    let mut callee_ctx = initial_context(&callee, input)
+        .with_context(|| format!("Invalid inputs for skill '{}'", callee.qualified_name()))?;
+
+    let nested_options = ExecutionOptions {
+        call_stack,
+        ..options.clone()
+    };
+    let mut callee_trace = ExecutionTrace::default();
+    let result = run_workflow(
+        &callee,
+        &mut callee_ctx,
+        llm,
+        &nested_options,
+        &mut callee_trace,
+    );
+    trace.usage = callee_trace.usage;
+    trace.callee = Some(Box::new(callee_trace));
+    let output = result?;
+
+    for (caller_var, callee_var) in &step.outputs {
+        let value = callee_ctx.get(callee_var).cloned().ok_or_else(|| {
+            anyhow!(
+                "Skill '{}' did not set '{callee_var}' for step '{}'",
+                callee.qualified_name(),
+                step.id
+            )
+        })?;
+        ctx.set(caller_var, value);
+    }
+    if let Some(var) = &step.output_var {
+        ctx.set(var, output.clone());
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use crate::llm::mock::MockLlmClient;
+    use crate::skill::model::{Permissions, Skill, StepType, WorkflowStep};
+    use crate::util::testing::test_skill;
+    use crate::workflow::executor::{ExecutionInput, ExecutionOptions, WorkflowExecutor};
+
+    fn skill(name: &str, run_commands: bool, steps: Vec<WorkflowStep>) -> Skill {
+        let mut skill = test_skill(name);
+        skill.metadata.inputs =
+            vec![serde_yaml::from_str("{ name: subject, type: string, default: world }").unwrap()];
+        if run_commands {
+            skill.metadata.permissions = Permissions {
+                run_commands: true,
+                allowed_runners: vec!["bash".to_string()],
+                allowed_paths: vec![],
+                network_access: false,
+                write_access: false,
+            };
+        }
+        skill.steps = steps;
+        skill
+    }
+
+    fn call(id: &str, target: &str) -> WorkflowStep {
+        WorkflowStep {
+            id: id.to_string(),
+            step_type: StepType::Skill,
+            skill: Some(target.to_string()),
+            output_var: Some("greeting".to_string()),
+            with: BTreeMap::from([("subject".to_string(), "{{user_input}}".to_string())]),
+            outputs: BTreeMap::from([("shout".to_string(), "loud".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn run(caller: &Skill, skills: Vec<Skill>) -> anyhow::Result<(String, WorkflowExecutor)> {
+        let options = ExecutionOptions {
+            skills: Arc::new(skills),
+            ..Default::default()
+        };
+        let mut executor = WorkflowExecutor::with_options(Box::new(MockLlmClient::new()), options);
+        let output = executor.execute(
+            caller,
+            ExecutionInput {
+                user_prompt: "genai".to_string(),
+                debug: false,
+                interactive: false,
+                inputs: BTreeMap::new(),
+            },
+        )?;
+        Ok((output, executor))
+    }
+
+    #[test]
+    fn captures_callee_outputs_and_nests_its_trace() {
+        let greet = skill(
+            "greet",
+            false,
+            vec![
+                WorkflowStep {
+                    id: "loud".to_string(),
+                    step_type: StepType::Output,
+                    template: Some("HELLO {{subject}}".to_string()),
+                    output_var: Some("loud".to_string()),
+                    ..Default::default()
+                },
+                WorkflowStep {
+                    id: "reply".to_string(),
+                    step_type: StepType::Output,
+                    template: Some("hello {{subject}}".to_string()),
+                    ..Default::default()
+                },
+            ],
+        );
+        let caller = skill(
+            "caller",
+            false,
+            vec![
+                call("greet", "greet"),
+                WorkflowStep {
+                    id: "done".to_string(),
+                    step_type: StepType::Output,
+                    template: Some("{{greeting}} / {{shout}}".to_string()),
+                    ..Default::default()
+                },
+            ],
+        );
+
+        let (output, executor) = run(&caller, vec![greet]).expect("call should succeed");
+        assert_eq!(output, "hello genai / HELLO genai");
+        let nested = executor.trace().steps[0].callee.as_ref().unwrap();
+        assert_eq!(nested.skill, "greet");
+        assert_eq!(nested.steps.len(), 2);
+    }
+
+    #[test]
+    fn command_steps_read_inputs_from_the_environment() {
+        let echo = skill(
+            "echo",
+            true,
+            vec![WorkflowStep {
+                id: "say".to_string(),
+                step_type: StepType::Command,
+                runner: Some("bash".to_string()),
+                cmd: Some("printf '%s {{subject}}' \"$GENAI_INPUT_SUBJECT\"".to_string()),
+                output_var: Some("loud".to_string()),
+                ..Default::default()
+            }],
+        );
+        let caller = skill("caller", true, vec![call("echo", "echo")]);
+
+        let (output, _) = run(&caller, vec![echo]).expect("call should succeed");
+        assert_eq!(output, "genai {{subject}}");
+    }
+
+    #[test]
+    fn callee_cannot_gain_permissions_or_recurse() {
+        let shell = skill(
+            "shell",
+            true,
+            vec![WorkflowStep {
+                id: "ls".to_string(),
+                step_type: StepType::Command,
+                runner: Some("bash".to_string()),
+                cmd: Some("ls".to_string()),
+                ..Default::default()
+            }],
+        );
+        let caller = skill("caller", false, vec![call("shell", "shell")]);
+        assert!(run(&caller, vec![shell]).is_err());
+
+        let ping = skill("ping", false, vec![call("pong", "pong")]);
+        let pong = skill("pong", false, vec![call("ping", "ping")]);
+        let err = match run(&ping, vec![ping.clone(), pong]) {
+            Ok(_) => panic!("cycle should be refused"),
+            Err(err) => err,
+        };
+        assert!(err.to_string().contains("cycle"), "{err:#}");
+    }
+}
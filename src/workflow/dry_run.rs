@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use crate::llm::client::{LlmClient, LlmRequest, LlmResponse};
+use crate::skill::model::Skill;
+use crate::workflow::executor::{ExecutionInput, ExecutionOptions, WorkflowExecutor};
+use crate::workflow::trace::ExecutionTrace;
+
+#[derive(Debug, Clone)]
+pub struct RenderedPrompt {
+    pub step_id: String,
+    pub model: String,
+    pub prompt: String,
+}
+
+/// Answers every llm call with an empty string instead of calling a model.
+struct SilentClient;
+
+impl LlmClient for SilentClient {
+    fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+        Ok(String::new())
+    }
+
+    fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse> {
+        Ok(LlmResponse {
+            candidates: vec![String::new()],
+            ..Default::default()
+        })
+    }
+}
+
+/// Runs command and output steps for real and renders each llm step's prompt without
+/// sending it. Steps downstream of an llm step see an empty value for its output.
+/// Prompts of called skills are listed under `caller-step/callee-step`.
+pub fn render_llm_prompts(
+    skill: &Skill,
+    input: ExecutionInput,
+    options: ExecutionOptions,
+) -> Result<Vec<RenderedPrompt>> {
+    let mut executor = WorkflowExecutor::with_options(Box::new(SilentClient), options);
+    executor.execute(skill, input)?;
+
+    let mut rendered = Vec::new();
+    collect_prompts(executor.trace(), "", &mut rendered);
+    Ok(rendered)
+}
+
+fn collect_prompts(trace: &ExecutionTrace, prefix: &str, out: &mut Vec<RenderedPrompt>) {
+    for step in &trace.steps {
+        let step_id = format!("{prefix}{}", step.id);
+        if let Some(callee) = &step.callee {
+            collect_prompts(callee, &format!("{step_id}/"), out);
+        }
+        if let (Some(model), Some(prompt)) = (&step.model, &step.prompt) {
+            out.push(RenderedPrompt {
+                step_id,
+                model: model.clone(),
+                prompt: prompt.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::render_llm_prompts;
+    use crate::skill::model::{StepType, WorkflowStep};
+    use crate::util::testing::test_skill;
+    use crate::workflow::executor::{ExecutionInput, ExecutionOptions};
+
+    fn llm(id: &str, prompt: &str) -> WorkflowStep {
+        WorkflowStep {
+            id: id.to_string(),
+            step_type: StepType::Llm,
+            model: Some("executor".to_string()),
+            prompt: Some(prompt.to_string()),
+            output_var: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lists_callee_prompts_under_the_calling_step() {
+        let mut notes = test_skill("notes");
+        notes.steps = vec![llm("draft", "notes for {{user_input}}")];
+        let mut caller = test_skill("caller");
+        caller.steps = vec![
+            llm("plan", "plan {{user_input}}"),
+            WorkflowStep {
+                id: "call".to_string(),
+                step_type: StepType::Skill,
+                skill: Some("notes".to_string()),
+                ..Default::default()
+            },
+            llm("gen", "gen {{plan}}"),
+        ];
+        let input = ExecutionInput {
+            user_prompt: "hi".to_string(),
+            debug: false,
+            interactive: false,
+            inputs: Default::default(),
+        };
+        let options = ExecutionOptions {
+            skills: Arc::new(vec![notes]),
+            ..Default::default()
+        };
+
+        let rendered = render_llm_prompts(&caller, input, options).unwrap();
+        let rendered = rendered
+            .iter()
+            .map(|p| (p.step_id.as_str(), p.prompt.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rendered,
+            vec![
+                ("plan", "plan hi"),
+                ("call/draft", "notes for hi"),
+                ("gen", "gen "),
+            ]
+        );
+    }
+}
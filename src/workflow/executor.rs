@@ -1,44 +1,133 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+
 use anyhow::Result;
 
 use crate::llm::client::LlmClient;
+use crate::skill::inputs::{check_path_inputs, resolve_inputs};
 use crate::skill::model::Skill;
+use crate::workflow::cache::CommandCache;
 use crate::workflow::condition::evaluate_if;
 use crate::workflow::context::ExecutionContext;
 use crate::workflow::step::execute_step;
+use crate::workflow::trace::{ExecutionTrace, StepTrace};
 
+#[derive(Debug, Clone)]
 pub struct ExecutionInput {
     pub user_prompt: String,
     pub debug: bool,
+    pub interactive: bool,
+    /// Values for the skill's declared inputs; defaults fill the rest.
+    pub inputs: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOptions {
+    /// Replaces the model (and routing) of every llm step, except `executor` steps and
+    /// steps of skills without `network_access`.
+    pub model_override: Option<String>,
+    /// Shared between executors so identical commands run once.
+    pub command_cache: Option<CommandCache>,
+    /// Skills that `type: skill` steps may call.
+    pub skills: Arc<Vec<Skill>>,
+    /// Qualified names of the skills currently executing, outermost first.
+    pub call_stack: Vec<String>,
 }
 
 pub struct WorkflowExecutor {
     llm: Box<dyn LlmClient>,
+    options: ExecutionOptions,
+    trace: ExecutionTrace,
 }
 
 impl WorkflowExecutor {
     pub fn new(llm: Box<dyn LlmClient>) -> Self {
-        Self { llm }
+        Self::with_options(llm, ExecutionOptions::default())
+    }
+
+    pub fn with_options(llm: Box<dyn LlmClient>, options: ExecutionOptions) -> Self {
+        Self {
+            llm,
+            options,
+            trace: ExecutionTrace::default(),
+        }
+    }
+
+    /// Trace of the most recent `execute` call.
+    pub fn trace(&self) -> &ExecutionTrace {
+        &self.trace
     }
 
     pub fn execute(&mut self, skill: &Skill, input: ExecutionInput) -> Result<String> {
-        let mut ctx = ExecutionContext::new();
-        ctx.set("user_input", input.user_prompt);
-        ctx.set("debug", input.debug.to_string());
+        self.trace = ExecutionTrace::default();
+        let mut ctx = initial_context(skill, input)?;
+        run_workflow(
+            skill,
+            &mut ctx,
+            self.llm.as_ref(),
+            &self.options,
+            &mut self.trace,
+        )
+    }
+}
 
-        let mut final_output = String::new();
+/// Context holding the built-in variables and the skill's resolved inputs.
+pub(crate) fn initial_context(skill: &Skill, input: ExecutionInput) -> Result<ExecutionContext> {
+    let inputs = resolve_inputs(&skill.metadata.inputs, &input.inputs, None)?;
+    check_path_inputs(
+        &skill.metadata.inputs,
+        &inputs,
+        &skill.metadata.permissions.allowed_paths,
+    )?;
 
-        for step in &skill.steps {
-            if let Some(expr) = &step.if_expr {
-                if !evaluate_if(expr, &ctx)? {
-                    continue;
-                }
-            }
+    let mut ctx = ExecutionContext::new();
+    for (name, value) in inputs {
+        ctx.set(name, value);
+    }
+    ctx.set("user_input", input.user_prompt);
+    ctx.set("debug", input.debug.to_string());
+    ctx.set("interactive", input.interactive.to_string());
+    Ok(ctx)
+}
+
+/// Runs every step of `skill` in `ctx`, recording into `trace`.
+pub(crate) fn run_workflow(
+    skill: &Skill,
+    ctx: &mut ExecutionContext,
+    llm: &dyn LlmClient,
+    options: &ExecutionOptions,
+    trace: &mut ExecutionTrace,
+) -> Result<String> {
+    *trace = ExecutionTrace {
+        skill: skill.metadata.name.clone(),
+        ..Default::default()
+    };
+    let started = Instant::now();
+    let mut final_output = String::new();
 
-            if let Some(out) = execute_step(step, &mut ctx, self.llm.as_ref())? {
-                final_output = out;
+    for step in &skill.steps {
+        let mut step_trace = StepTrace::new(&step.id, step.step_type.as_str());
+
+        if let Some(expr) = &step.if_expr {
+            if !evaluate_if(expr, ctx)? {
+                step_trace.skipped = true;
+                trace.steps.push(step_trace);
+                continue;
             }
         }
 
-        Ok(final_output)
+        let step_started = Instant::now();
+        let result = execute_step(skill, step, ctx, llm, options, &mut step_trace);
+        step_trace.duration_ms = step_started.elapsed().as_millis();
+        trace.usage.add(&step_trace.usage);
+        trace.steps.push(step_trace);
+
+        if let Some(out) = result? {
+            final_output = out;
+        }
     }
+
+    trace.duration_ms = started.elapsed().as_millis();
+    Ok(final_output)
 }
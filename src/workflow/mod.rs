@@ -1,4 +1,12 @@
+pub mod cache;
+pub mod compare;
+pub mod compose;
 pub mod condition;
 pub mod context;
+pub mod dry_run;
 pub mod executor;
+pub mod plan;
+pub mod routing;
+pub mod sampling;
 pub mod step;
+pub mod trace;
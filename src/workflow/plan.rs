@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::skill::planner::PlannedCall;
+use crate::util::templating::render_template;
+use crate::workflow::executor::{ExecutionInput, WorkflowExecutor};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanStepResult {
+    pub skill: String,
+    pub output: String,
+}
+
+/// Runs a confirmed plan in order. Each step's prompt and inputs are rendered with
+/// `user_input`, `previous_output` and `stepN_output`; a step without a prompt gets the
+/// request, followed by the previous step's output after the first step.
+pub fn execute_plan(
+    plan: &[PlannedCall],
+    user_input: &str,
+    executor: &mut WorkflowExecutor,
+    debug: bool,
+    interactive: bool,
+) -> Result<Vec<PlanStepResult>> {
+    let mut vars = HashMap::from([
+        ("user_input".to_string(), user_input.to_string()),
+        ("previous_output".to_string(), String::new()),
+    ]);
+    let mut results = Vec::with_capacity(plan.len());
+
+    for (i, call) in plan.iter().enumerate() {
+        let name = call.skill.qualified_name();
+        let prompt = match &call.prompt {
+            Some(template) => render_template(template, &vars)?,
+            None if i == 0 => user_input.to_string(),
+            None => format!(
+                "{user_input}\n\nOutput of the previous step:\n{}",
+                vars["previous_output"]
+            ),
+        };
+        let inputs = call
+            .inputs
+            .iter()
+            .map(|(key, template)| Ok((key.clone(), render_template(template, &vars)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        let output = executor
+            .execute(
+                call.skill,
+                ExecutionInput {
+                    user_prompt: prompt,
+                    debug,
+                    interactive,
+                    inputs,
+                },
+            )
+            .with_context(|| format!("Plan step {} ({name}) failed", i + 1))?;
+
+        vars.insert(format!("step{}_output", i + 1), output.clone());
+        vars.insert("previous_output".to_string(), output.clone());
+        results.push(PlanStepResult {
+            skill: name,
+            output,
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::execute_plan;
+    use crate::llm::mock::MockLlmClient;
+    use crate::skill::model::{Skill, StepType, WorkflowStep};
+    use crate::skill::planner::PlannedCall;
+    use crate::util::testing::test_skill;
+    use crate::workflow::executor::WorkflowExecutor;
+
+    fn echo_skill(name: &str) -> Skill {
+        let mut skill = test_skill(name);
+        skill.steps = vec![WorkflowStep {
+            id: "echo".to_string(),
+            step_type: StepType::Llm,
+            model: Some(name.to_string()),
+            prompt: Some("{{user_input}}".to_string()),
+            output_var: Some("out".to_string()),
+            ..Default::default()
+        }];
+        skill
+    }
+
+    #[test]
+    fn threads_outputs_between_skills() {
+        let commit = echo_skill("commit");
+        let pr = echo_skill("pr");
+        let review = echo_skill("review");
+        let plan = vec![
+            PlannedCall {
+                skill: &commit,
+                prompt: None,
+                inputs: BTreeMap::new(),
+                reason: String::new(),
+            },
+            PlannedCall {
+                skill: &pr,
+                prompt: None,
+                inputs: BTreeMap::new(),
+                reason: String::new(),
+            },
+            PlannedCall {
+                skill: &review,
+                prompt: Some("check {{step1_output}}".to_string()),
+                inputs: BTreeMap::new(),
+                reason: String::new(),
+            },
+        ];
+
+        let mut executor = WorkflowExecutor::new(Box::new(MockLlmClient::new()));
+        let results = execute_plan(&plan, "ship it", &mut executor, false, false).unwrap();
+        assert_eq!(results[0].output, "[mock:commit] ship it");
+        assert_eq!(
+            results[1].output,
+            "[mock:pr] ship it\n\nOutput of the previous step:\n[mock:commit] ship it"
+        );
+        assert_eq!(
+            results[2].output,
+            "[mock:review] check [mock:commit] ship it"
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Result};
+
+use crate::llm::tokens::estimate_tokens;
+use crate::skill::model::RoutingPolicy;
+use crate::workflow::context::ExecutionContext;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutedModel {
+    pub model: String,
+    pub measured_tokens: u64,
+}
+
+/// Returns the first rule whose bound exceeds the measured input size.
+pub fn route_model(
+    policy: &RoutingPolicy,
+    rendered_prompt: &str,
+    ctx: &ExecutionContext,
+) -> Result<RoutedModel> {
+    let (label, text) = match &policy.measure {
+        Some(var) => (
+            var.as_str(),
+            ctx.get(var).map(String::as_str).unwrap_or_default(),
+        ),
+        None => ("prompt", rendered_prompt),
+    };
+    let measured_tokens = estimate_tokens(text);
+
+    policy
+        .rules
+        .iter()
+        .find(|rule| measured_tokens < rule.below)
+        .map(|rule| RoutedModel {
+            model: rule.model.clone(),
+            measured_tokens,
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Input too large: {measured_tokens} tokens of {label} exceeds every routing rule"
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::route_model;
+    use crate::skill::model::{RoutingPolicy, RoutingRule};
+    use crate::workflow::context::ExecutionContext;
+
+    fn policy(measure: Option<&str>) -> RoutingPolicy {
+        RoutingPolicy {
+            measure: measure.map(str::to_string),
+            rules: vec![
+                RoutingRule {
+                    below: 10,
+                    model: "fast".to_string(),
+                },
+                RoutingRule {
+                    below: 100,
+                    model: "smart".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn routes_by_measured_variable() {
+        let mut ctx = ExecutionContext::new();
+        ctx.set("diff", "x".repeat(200));
+
+        let routed = route_model(&policy(Some("diff")), "short prompt", &ctx).unwrap();
+        assert_eq!(routed.model, "smart");
+        assert_eq!(routed.measured_tokens, 50);
+
+        let routed = route_model(&policy(None), "short prompt", &ctx).unwrap();
+        assert_eq!(routed.model, "fast");
+    }
+
+    #[test]
+    fn refuses_inputs_above_every_rule() {
+        let mut ctx = ExecutionContext::new();
+        ctx.set("diff", "x".repeat(1000));
+
+        let err = route_model(&policy(Some("diff")), "", &ctx).unwrap_err();
+        assert!(err.to_string().contains("250 tokens of diff"), "{err}");
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use tracing::{debug, warn};
+
+use crate::llm::client::{LlmClient, LlmRequest, Usage};
+use crate::skill::model::{Sampling, SamplingStrategy};
+use crate::util::templating::render_template;
+use crate::workflow::context::ExecutionContext;
+
+const DEFAULT_JUDGE_PROMPT: &str = "Pick the best answer among the candidates below. \
+Reply with the candidate number only.\n\n{{candidates}}";
+
+/// Picks one of `candidates`. Tokens spent by a judge are added to `usage`.
+pub fn choose_candidate(
+    sampling: &Sampling,
+    candidates: Vec<String>,
+    step_model: &str,
+    ctx: &ExecutionContext,
+    llm: &dyn LlmClient,
+    usage: &mut Usage,
+) -> Result<String> {
+    if candidates.len() <= 1 {
+        return candidates
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("LLM returned no candidates"));
+    }
+
+    let index = match sampling.strategy {
+        SamplingStrategy::Majority => majority_index(&candidates),
+        SamplingStrategy::Shortest => {
+            shortest_valid_index(&candidates, sampling.pattern.as_deref())?
+        }
+        SamplingStrategy::Judge => judge_index(sampling, &candidates, step_model, ctx, llm, usage)?,
+        SamplingStrategy::Pick => {
+            if ctx.get("interactive").map(String::as_str) == Some("true") {
+                prompt_user_pick(&candidates)?
+            } else {
+                warn!("strategy=pick needs an interactive terminal, using majority vote");
+                majority_index(&candidates)
+            }
+        }
+    };
+
+    debug!(strategy = ?sampling.strategy, index, total = candidates.len(), "Chose candidate");
+    Ok(candidates[index].clone())
+}
+
+fn normalize(candidate: &str) -> String {
+    candidate.trim().to_lowercase()
+}
+
+/// Most frequent normalized answer; ties go to the earliest candidate.
+fn majority_index(candidates: &[String]) -> usize {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for candidate in candidates {
+        *counts.entry(normalize(candidate)).or_default() += 1;
+    }
+
+    let mut best = 0;
+    for (index, candidate) in candidates.iter().enumerate() {
+        if counts[&normalize(candidate)] > counts[&normalize(&candidates[best])] {
+            best = index;
+        }
+    }
+    best
+}
+
+fn shortest_valid_index(candidates: &[String], pattern: Option<&str>) -> Result<usize> {
+    let re = pattern
+        .map(Regex::new)
+        .transpose()
+        .context("Invalid sampling pattern")?;
+
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| re.as_ref().is_none_or(|re| re.is_match(c.trim())))
+        .min_by_key(|(_, c)| c.trim().chars().count())
+        .map(|(index, _)| index)
+        .ok_or_else(|| anyhow!("No candidate matched sampling pattern"))
+}
+
+fn numbered(candidates: &[String]) -> String {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("[{}]\n{}", i + 1, c.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn judge_index(
+    sampling: &Sampling,
+    candidates: &[String],
+    step_model: &str,
+    ctx: &ExecutionContext,
+    llm: &dyn LlmClient,
+    usage: &mut Usage,
+) -> Result<usize> {
+    let mut vars = ctx.as_map().clone();
+    vars.insert("candidates".to_string(), numbered(candidates));
+
+    let template = sampling
+        .judge_prompt
+        .as_deref()
+        .unwrap_or(DEFAULT_JUDGE_PROMPT);
+    let prompt = render_template(template, &vars)?;
+    let model = sampling.judge_model.as_deref().unwrap_or(step_model);
+    let response = llm.complete(&LlmRequest::new(model, &prompt))?;
+    usage.add(&response.usage);
+    let verdict = response.candidates.into_iter().next().unwrap_or_default();
+
+    match parse_choice(&verdict, candidates.len()) {
+        Some(index) => Ok(index),
+        None => {
+            warn!(
+                "Judge answer '{}' is not a candidate number, using majority vote",
+                verdict.trim()
+            );
+            Ok(majority_index(candidates))
+        }
+    }
+}
+
+/// Extracts the first 1-based number in `answer` that refers to a candidate.
+fn parse_choice(answer: &str, total: usize) -> Option<usize> {
+    answer
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|token| token.parse::<usize>().ok())
+        .find(|n| (1..=total).contains(n))
+        .map(|n| n - 1)
+}
+
+fn prompt_user_pick(candidates: &[String]) -> Result<usize> {
+    let mut stderr = std::io::stderr();
+    writeln!(stderr, "{}\n", numbered(candidates))?;
+    loop {
+        write!(stderr, "Choose a candidate [1-{}]: ", candidates.len())?;
+        stderr.flush()?;
+
+        let mut line = String::new();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            return Err(anyhow!("No candidate chosen"));
+        }
+        if let Some(index) = parse_choice(&line, candidates.len()) {
+            return Ok(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{choose_candidate, majority_index, parse_choice, shortest_valid_index};
+    use crate::llm::client::{LlmClient, LlmRequest, LlmResponse, Usage};
+    use crate::skill::model::{Sampling, SamplingStrategy};
+    use crate::workflow::context::ExecutionContext;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn majority_ignores_case_and_whitespace() {
+        let candidates = strings(&["feat: a", "fix: b", " FIX: b\n", "feat: c"]);
+        assert_eq!(majority_index(&candidates), 1);
+    }
+
+    #[test]
+    fn shortest_respects_pattern() {
+        let candidates = strings(&["update", "fix(core): handle empty diff", "feat: x"]);
+        let index = shortest_valid_index(&candidates, Some(r"^(feat|fix)(\(.+\))?: ")).unwrap();
+        assert_eq!(index, 2);
+        assert!(shortest_valid_index(&candidates, Some("^docs")).is_err());
+    }
+
+    #[test]
+    fn judge_choice_must_be_in_range() {
+        assert_eq!(parse_choice("Candidate 2 is best", 3), Some(1));
+        assert_eq!(parse_choice("7, then 3", 3), Some(2));
+        assert_eq!(parse_choice("none", 3), None);
+    }
+
+    struct Judge;
+
+    impl LlmClient for Judge {
+        fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+            unreachable!()
+        }
+
+        fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse> {
+            Ok(LlmResponse {
+                candidates: vec!["2".to_string()],
+                usage: Usage {
+                    prompt_tokens: 30,
+                    output_tokens: 1,
+                    thinking_tokens: 0,
+                    total_tokens: 31,
+                },
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn judge_tokens_count_towards_the_step() {
+        let sampling: Sampling = serde_yaml::from_str("{ n: 2, strategy: judge }").unwrap();
+        assert_eq!(sampling.strategy, SamplingStrategy::Judge);
+        let mut usage = Usage {
+            prompt_tokens: 10,
+            output_tokens: 20,
+            thinking_tokens: 0,
+            total_tokens: 30,
+        };
+
+        let chosen = choose_candidate(
+            &sampling,
+            strings(&["a", "b"]),
+            "m",
+            &ExecutionContext::new(),
+            &Judge,
+            &mut usage,
+        )
+        .unwrap();
+
+        assert_eq!(chosen, "b");
+        assert_eq!(usage.prompt_tokens, 40);
+        assert_eq!(usage.total_tokens, 61);
+    }
+}
@@ -1,14 +1,23 @@
 use anyhow::{anyhow, Result};
 
-use crate::llm::client::LlmClient;
-use crate::skill::model::{StepType, WorkflowStep};
+use crate::llm::client::{LlmClient, LlmRequest, ThinkingConfig};
+use crate::skill::inputs::input_env_name;
+use crate::skill::model::{Skill, StepType, WorkflowStep};
 use crate::util::templating::render_template;
+use crate::workflow::compose::call_skill;
 use crate::workflow::context::ExecutionContext;
+use crate::workflow::executor::ExecutionOptions;
+use crate::workflow::routing::route_model;
+use crate::workflow::sampling::choose_candidate;
+use crate::workflow::trace::StepTrace;
 
 pub fn execute_step(
+    skill: &Skill,
     step: &WorkflowStep,
     ctx: &mut ExecutionContext,
     llm: &dyn LlmClient,
+    options: &ExecutionOptions,
+    trace: &mut StepTrace,
 ) -> Result<Option<String>> {
     match step.step_type {
         StepType::Command => {
@@ -21,31 +30,74 @@ pub fn execute_step(
                 .as_deref()
                 .ok_or_else(|| anyhow!("Command step missing cmd"))?;
 
-            let output = match runner {
-                "bash" => std::process::Command::new("bash")
-                    .arg("-lc")
-                    .arg(cmd)
-                    .output()?,
-                _ => return Err(anyhow!("Unsupported runner: {runner}")),
+            let env = input_env(skill, ctx);
+            let run = || run_command(runner, cmd, &env);
+            let stdout = match &options.command_cache {
+                Some(cache) => cache.get_or_run(runner, cmd, &env, run)?,
+                None => run()?,
             };
-
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
             if let Some(var) = &step.output_var {
                 ctx.set(var, stdout.clone());
             }
             Ok(Some(stdout))
         }
         StepType::Llm => {
-            let model = step
-                .model
-                .as_deref()
-                .ok_or_else(|| anyhow!("LLM step missing model"))?;
             let prompt = step
                 .prompt
                 .as_deref()
                 .ok_or_else(|| anyhow!("LLM step missing prompt"))?;
             let rendered_prompt = render_template(prompt, ctx.as_map())?;
-            let response = llm.generate(model, &rendered_prompt)?;
+
+            // Offline skills and `executor` steps keep their model so an override cannot
+            // reach the network; callees inherit the caller's `network_access`.
+            let model_override = options.model_override.as_ref().filter(|_| {
+                skill.metadata.permissions.network_access
+                    && step.model.as_deref() != Some("executor")
+            });
+            let routed;
+            let model = match (model_override, &step.routing) {
+                (Some(model), _) => model.as_str(),
+                (None, Some(policy)) => {
+                    routed = route_model(policy, &rendered_prompt, ctx)?;
+                    trace.routed_tokens = Some(routed.measured_tokens);
+                    routed.model.as_str()
+                }
+                (None, None) => step
+                    .model
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("LLM step missing model"))?,
+            };
+
+            let mut request = LlmRequest::new(model, &rendered_prompt);
+            request.candidate_count = step.sampling.as_ref().map_or(1, |s| s.n);
+            if step.thinking_budget.is_some() || step.include_thoughts.is_some() {
+                request.thinking = Some(ThinkingConfig {
+                    budget: step.thinking_budget,
+                    include_thoughts: step.include_thoughts.unwrap_or(false),
+                });
+            }
+
+            let completion = llm.complete(&request)?;
+            trace.model = Some(model.to_string());
+            trace.prompt = Some(request.prompt);
+            trace.usage = completion.usage;
+            trace.thoughts = completion.thoughts;
+
+            let response = match &step.sampling {
+                Some(sampling) => choose_candidate(
+                    sampling,
+                    completion.candidates,
+                    model,
+                    ctx,
+                    llm,
+                    &mut trace.usage,
+                )?,
+                None => completion
+                    .candidates
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow!("LLM returned no candidates"))?,
+            };
             if let Some(var) = &step.output_var {
                 ctx.set(var, response.clone());
             }
@@ -62,5 +114,35 @@ pub fn execute_step(
             }
             Ok(Some(rendered))
         }
+        StepType::Skill => call_skill(skill, step, ctx, llm, options, trace).map(Some),
     }
 }
+
+/// `cmd` is run as written, never templated; declared inputs reach it as
+/// `GENAI_INPUT_<NAME>` environment variables so their values are never parsed as shell.
+fn input_env(skill: &Skill, ctx: &ExecutionContext) -> Vec<(String, String)> {
+    skill
+        .metadata
+        .inputs
+        .iter()
+        .map(|spec| {
+            (
+                input_env_name(&spec.name),
+                ctx.get(&spec.name).cloned().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+fn run_command(runner: &str, cmd: &str, env: &[(String, String)]) -> Result<String> {
+    let output = match runner {
+        "bash" => std::process::Command::new("bash")
+            .arg("-lc")
+            .arg(cmd)
+            .envs(env.iter().map(|(key, value)| (key, value)))
+            .output()?,
+        _ => return Err(anyhow!("Unsupported runner: {runner}")),
+    };
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+use crate::llm::client::Usage;
+
+/// Per-run record of what each step did, printed with `--debug`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExecutionTrace {
+    pub skill: String,
+    pub steps: Vec<StepTrace>,
+    pub usage: Usage,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StepTrace {
+    pub id: String,
+    pub step_type: String,
+    pub skipped: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routed_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub thoughts: Vec<String>,
+    /// The rendered prompt of an llm step; left out of `--debug` output.
+    #[serde(skip)]
+    pub prompt: Option<String>,
+    /// The called skill's own trace, for `type: skill` steps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callee: Option<Box<ExecutionTrace>>,
+    pub usage: Usage,
+    pub duration_ms: u128,
+}
+
+impl StepTrace {
+    pub fn new(id: &str, step_type: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            step_type: step_type.to_string(),
+            ..Default::default()
+        }
+    }
+}